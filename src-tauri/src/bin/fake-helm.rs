@@ -0,0 +1,514 @@
+//! Emulates helm subcommands used by Helmik, so the application can be tested without a cluster or network.
+//!
+//! State (repositories, available charts and installed releases) is kept in a JSON file
+//! pointed by `FAKE_HELM_STATE`. Charts become searchable after a repository with URL
+//! from `catalog` is added. Set the file path as helm binary in settings to use it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+use serde::{Deserialize, Serialize};
+
+const STATE_VARIABLE: &'static str = "FAKE_HELM_STATE";
+const VERSION: &'static str = "v3.12.0";
+
+/// Flags followed by a value, all other flags are switches.
+const VALUE_FLAGS: [&'static str; 11] = ["--kube-context", "-n", "--namespace", "-o", "--output", "--version", "-f", "--values", "--template", "--max", "--timeout"];
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct State {
+    #[serde(default)]
+    repositories: Vec<Repository>,
+    /// Charts served by repository URL.
+    #[serde(default)]
+    catalog: BTreeMap<String, Vec<Chart>>,
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Repository {
+    name: String,
+    url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Chart {
+    name: String,
+    version: String,
+    #[serde(rename = "appVersion", default)]
+    app_version: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    readme: String,
+    #[serde(default)]
+    values: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Release {
+    name: String,
+    namespace: String,
+    revisions: Vec<Revision>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Revision {
+    revision: u32,
+    updated: chrono::DateTime<chrono::Utc>,
+    status: String,
+    chart: String,
+    #[serde(rename = "appVersion")]
+    app_version: String,
+    /// User supplied values as YAML.
+    values: String,
+    description: String,
+}
+
+impl Release {
+    fn current(&self) -> &Revision {
+        self.revisions.last().expect("Release without revisions")
+    }
+
+    fn add_revision(&mut self, chart: &Chart, values: String, description: String) {
+        for revision in self.revisions.iter_mut() {
+            revision.status = "superseded".to_owned();
+        }
+        self.revisions.push(Revision {
+            revision: self.revisions.len() as u32 + 1,
+            updated: chrono::Utc::now(),
+            status: "deployed".to_owned(),
+            chart: format!("{}-{}", chart.name, chart.version),
+            app_version: chart.app_version.clone(),
+            values,
+            description,
+        });
+    }
+}
+
+/// Positional arguments and flags of single invocation.
+struct Args {
+    positional: Vec<String>,
+    flags: BTreeMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>) -> Self {
+        let mut positional = vec![];
+        let mut flags = BTreeMap::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if let Some((name, value)) = arg.split_once('=').filter(|_| arg.starts_with("--")) {
+                flags.insert(name.to_owned(), value.to_owned());
+            } else if VALUE_FLAGS.contains(&arg.as_str()) {
+                flags.insert(arg, args.next().unwrap_or_default());
+            } else if arg.starts_with('-') {
+                flags.insert(arg, "true".to_owned());
+            } else {
+                positional.push(arg);
+            }
+        }
+        Args { positional, flags }
+    }
+
+    fn flag(&self, names: &[&str]) -> Option<&str> {
+        names.iter().find_map(|n| self.flags.get(*n)).map(|v| v.as_str())
+    }
+
+    fn namespace(&self) -> String {
+        self.flag(&["-n", "--namespace"]).unwrap_or("default").to_owned()
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<String, String> {
+        self.positional.get(index).cloned().ok_or(format!("\"{}\" required", name))
+    }
+}
+
+fn main() {
+    match run(env::args().skip(1).collect()) {
+        Ok(output) => print!("{}", output),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<String, String> {
+    let path = env::var(STATE_VARIABLE).map(PathBuf::from).map_err(|_| format!("{} is not set", STATE_VARIABLE))?;
+    let mut state = load(&path)?;
+    let output = execute(&mut state, Args::parse(args))?;
+    save(&path, &state)?;
+    Ok(output)
+}
+
+fn load(path: &Path) -> Result<State, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(content.as_str()).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(State::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn save(path: &Path, state: &State) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn execute(state: &mut State, args: Args) -> Result<String, String> {
+    let command = args.positional.iter().take(2).map(|a| a.as_str()).collect::<Vec<&str>>();
+    match command.as_slice() {
+        ["version", ..] => Ok(VERSION.to_owned()),
+        ["env", ..] => Ok(format!("HELM_BIN=\"fake-helm\"\nHELM_NAMESPACE=\"default\"\nHELM_KUBECONTEXT=\"{}\"\n", args.flag(&["--kube-context"]).unwrap_or_default())),
+        ["repo", "add"] => repo_add(state, &args),
+        ["repo", "ls"] | ["repo", "list"] => repo_list(state),
+        ["repo", "remove"] | ["repo", "rm"] => repo_remove(state, &args),
+        ["repo", "update"] => Ok("Update Complete. ⎈Happy Helming!⎈\n".to_owned()),
+        ["search", "repo"] => search(state, &args),
+        ["show", "chart"] => show(state, &args, chart_yaml),
+        ["show", "readme"] => show(state, &args, |c| Ok(c.readme.clone())),
+        ["show", "values"] => show(state, &args, |c| Ok(c.values.clone())),
+        ["install", ..] => install(state, &args),
+        ["upgrade", ..] => upgrade(state, &args),
+        ["list", ..] | ["ls", ..] => list(state, &args),
+        ["uninstall", ..] => uninstall(state, &args),
+        ["get", "values"] => get_values(state, &args),
+        ["history", ..] => history(state, &args),
+        ["rollback", ..] => rollback(state, &args),
+        _ => Err(format!("unknown command \"{}\" for \"helm\"", args.positional.join(" "))),
+    }
+}
+
+fn repo_add(state: &mut State, args: &Args) -> Result<String, String> {
+    let name = args.positional(2, "name")?;
+    let url = args.positional(3, "url")?;
+    if state.repositories.iter().any(|r| r.name == name) {
+        return Err(format!("repository name ({}) already exists, please specify a different name", name));
+    }
+    if !state.catalog.contains_key(&url) {
+        return Err(format!("looks like \"{}\" is not a valid chart repository or cannot be reached", url));
+    }
+    state.repositories.push(Repository { name: name.clone(), url });
+    Ok(format!("\"{}\" has been added to your repositories\n", name))
+}
+
+fn repo_list(state: &State) -> Result<String, String> {
+    if state.repositories.is_empty() {
+        return Err("no repositories to show".to_owned());
+    }
+    serde_json::to_string(&state.repositories).map_err(|e| e.to_string())
+}
+
+fn repo_remove(state: &mut State, args: &Args) -> Result<String, String> {
+    let name = args.positional(2, "name")?;
+    let count = state.repositories.len();
+    state.repositories.retain(|r| r.name != name);
+    match state.repositories.len() == count {
+        true => Err(format!("no repo named \"{}\" found", name)),
+        false => Ok(format!("\"{}\" has been removed from your repositories\n", name)),
+    }
+}
+
+/// Newest version of every chart from added repositories, named `repository/chart`.
+fn available_charts(state: &State) -> Vec<(String, Chart)> {
+    let mut charts: BTreeMap<String, Chart> = BTreeMap::new();
+    for repository in state.repositories.iter() {
+        for chart in state.catalog.get(&repository.url).into_iter().flatten() {
+            let name = format!("{}/{}", repository.name, chart.name);
+            let newer = charts.get(&name).map(|c| is_newer(chart, c)).unwrap_or(true);
+            if newer {
+                charts.insert(name, chart.clone());
+            }
+        }
+    }
+    charts.into_iter().collect()
+}
+
+/// Every version of every chart from added repositories, named `repository/chart`, newest first.
+fn all_charts(state: &State) -> Vec<(String, Chart)> {
+    let mut charts = state.repositories.iter()
+        .flat_map(|repository| state.catalog.get(&repository.url).into_iter().flatten()
+            .map(|chart| (format!("{}/{}", repository.name, chart.name), chart.clone())))
+        .collect::<Vec<(String, Chart)>>();
+    charts.sort_by(|(a, chart_a), (b, chart_b)| a.cmp(b).then_with(|| match is_newer(chart_a, chart_b) {
+        true => std::cmp::Ordering::Less,
+        false => std::cmp::Ordering::Greater,
+    }));
+    charts
+}
+
+fn is_newer(chart: &Chart, than: &Chart) -> bool {
+    match (semver::Version::parse(chart.version.as_str()), semver::Version::parse(than.version.as_str())) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => chart.version > than.version,
+    }
+}
+
+/// Chart referenced as `repository/chart`, given version or the newest one.
+fn find_chart(state: &State, reference: &str, version: Option<&str>) -> Result<Chart, String> {
+    let not_found = || format!("chart \"{}\" not found", reference);
+    let (repository_name, chart_name) = reference.split_once('/').ok_or_else(not_found)?;
+    let repository = state.repositories.iter()
+        .find(|r| r.name == repository_name)
+        .ok_or(format!("repo {} not found", repository_name))?;
+    let mut charts = state.catalog.get(&repository.url).into_iter().flatten().filter(|c| c.name == chart_name);
+
+    match version {
+        Some(version) => charts.rfind(|c| c.version == version).cloned()
+            .ok_or(format!("chart \"{}\" version \"{}\" not found in {} repository", chart_name, version, repository_name)),
+        None => charts.fold(None, |newest: Option<&Chart>, c| match newest {
+            Some(n) if !is_newer(c, n) => Some(n),
+            _ => Some(c),
+        }).cloned().ok_or_else(not_found),
+    }
+}
+
+fn search(state: &State, args: &Args) -> Result<String, String> {
+    let filter = args.positional.get(2).map(|f| f.to_lowercase()).unwrap_or_default();
+    let charts = match args.flags.contains_key("--versions") {
+        true => all_charts(state),
+        false => available_charts(state),
+    };
+    let charts = charts.into_iter()
+        .filter(|(name, _)| name.to_lowercase().contains(filter.as_str()))
+        .map(|(name, chart)| serde_json::json!({
+            "name": name,
+            "version": chart.version,
+            "app_version": chart.app_version,
+            "description": chart.description,
+        }))
+        .collect::<Vec<serde_json::Value>>();
+    serde_json::to_string(&charts).map_err(|e| e.to_string())
+}
+
+fn show<F>(state: &State, args: &Args, output: F) -> Result<String, String>
+    where
+        F: FnOnce(&Chart) -> Result<String, String>,
+{
+    let chart = find_chart(state, args.positional(2, "chart")?.as_str(), args.flag(&["--version"]))?;
+    output(&chart)
+}
+
+fn chart_yaml(chart: &Chart) -> Result<String, String> {
+    serde_yaml::to_string(&serde_json::json!({
+        "apiVersion": "v2",
+        "appVersion": chart.app_version,
+        "description": chart.description,
+        "name": chart.name,
+        "type": "application",
+        "version": chart.version,
+    })).map_err(|e| e.to_string())
+}
+
+fn values(args: &Args) -> Result<String, String> {
+    match args.flag(&["-f", "--values"]) {
+        Some(file) => fs::read_to_string(file).map_err(|e| format!("open {}: {}", file, e)),
+        None => Ok("".to_owned()),
+    }
+}
+
+fn find_release<'a>(state: &'a mut State, name: &str, namespace: &str) -> Result<&'a mut Release, String> {
+    state.releases.iter_mut()
+        .find(|r| r.name == name && r.namespace == namespace)
+        .ok_or("release: not found".to_owned())
+}
+
+fn install(state: &mut State, args: &Args) -> Result<String, String> {
+    let name = args.positional(1, "name")?;
+    let namespace = args.namespace();
+    if state.releases.iter().any(|r| r.name == name && r.namespace == namespace) {
+        return Err("INSTALLATION FAILED: cannot re-use a name that is still in use".to_owned());
+    }
+    let chart = find_chart(state, args.positional(2, "chart")?.as_str(), args.flag(&["--version"]))
+        .map_err(|e| format!("INSTALLATION FAILED: {}", e))?;
+
+    let mut release = Release { name: name.clone(), namespace: namespace.clone(), revisions: vec![] };
+    release.add_revision(&chart, values(args)?, "Install complete".to_owned());
+    state.releases.push(release);
+    Ok(format!("NAME: {}\nNAMESPACE: {}\nSTATUS: deployed\nREVISION: 1\n", name, namespace))
+}
+
+fn upgrade(state: &mut State, args: &Args) -> Result<String, String> {
+    let name = args.positional(1, "name")?;
+    let installed = state.releases.iter().any(|r| r.name == name && r.namespace == args.namespace());
+    if !installed && args.flags.contains_key("--install") {
+        return install(state, args);
+    }
+    let chart = find_chart(state, args.positional(2, "chart")?.as_str(), args.flag(&["--version"]))
+        .map_err(|e| format!("UPGRADE FAILED: {}", e))?;
+    let values = values(args)?;
+
+    let release = find_release(state, name.as_str(), args.namespace().as_str())
+        .map_err(|_| format!("UPGRADE FAILED: \"{}\" has no deployed releases", name))?;
+    let values = match args.flags.contains_key("--reuse-values") && values.is_empty() {
+        true => release.current().values.clone(),
+        false => values,
+    };
+    release.add_revision(&chart, values, "Upgrade complete".to_owned());
+    Ok(format!("Release \"{}\" has been upgraded. Happy Helming!\nREVISION: {}\n", name, release.current().revision))
+}
+
+fn list(state: &State, args: &Args) -> Result<String, String> {
+    let all_namespaces = args.flags.contains_key("-A") || args.flags.contains_key("--all-namespaces");
+    let namespace = args.namespace();
+    let releases = state.releases.iter()
+        .filter(|r| all_namespaces || r.namespace == namespace)
+        .map(|r| {
+            let current = r.current();
+            serde_json::json!({
+                "name": r.name,
+                "namespace": r.namespace,
+                "revision": current.revision.to_string(),
+                "updated": current.updated.format("%Y-%m-%d %H:%M:%S.%f +0000 UTC").to_string(),
+                "status": current.status,
+                "chart": current.chart,
+                "app_version": current.app_version,
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+    serde_json::to_string(&releases).map_err(|e| e.to_string())
+}
+
+fn uninstall(state: &mut State, args: &Args) -> Result<String, String> {
+    let name = args.positional(1, "name")?;
+    let namespace = args.namespace();
+    find_release(state, name.as_str(), namespace.as_str())
+        .map_err(|_| format!("uninstall: Release not loaded: {}: release: not found", name))?;
+    state.releases.retain(|r| !(r.name == name && r.namespace == namespace));
+    Ok(format!("release \"{}\" uninstalled\n", name))
+}
+
+fn get_values(state: &mut State, args: &Args) -> Result<String, String> {
+    let release = find_release(state, args.positional(2, "name")?.as_str(), args.namespace().as_str())?;
+    match release.current().values.trim().is_empty() {
+        true => Ok("null\n".to_owned()),
+        false => Ok(release.current().values.clone()),
+    }
+}
+
+fn history(state: &mut State, args: &Args) -> Result<String, String> {
+    let release = find_release(state, args.positional(1, "name")?.as_str(), args.namespace().as_str())?;
+    let revisions = release.revisions.iter()
+        .map(|r| serde_json::json!({
+            "revision": r.revision,
+            "updated": r.updated.to_rfc3339(),
+            "status": r.status,
+            "chart": r.chart,
+            "app_version": r.app_version,
+            "description": r.description,
+        }))
+        .collect::<Vec<serde_json::Value>>();
+    serde_json::to_string(&revisions).map_err(|e| e.to_string())
+}
+
+fn rollback(state: &mut State, args: &Args) -> Result<String, String> {
+    let release = find_release(state, args.positional(1, "name")?.as_str(), args.namespace().as_str())?;
+    let current = release.current().revision;
+    let target = match args.positional.get(2) {
+        Some(revision) => revision.parse::<u32>().map_err(|e| format!("invalid revision {}: {}", revision, e))?,
+        None => 0,
+    };
+    // like helm, revision 0 stands for the previous one which first revision does not have
+    let target = match target {
+        0 => current - 1,
+        revision => revision,
+    };
+    let target = release.revisions.iter()
+        .find(|r| r.revision == target)
+        .cloned()
+        .ok_or(format!("release has no {} version", target))?;
+
+    let (name, version) = target.chart.rsplit_once('-').unwrap_or((target.chart.as_str(), ""));
+    let chart = Chart {
+        name: name.to_owned(),
+        version: version.to_owned(),
+        app_version: target.app_version.clone(),
+        description: "".to_owned(),
+        readme: "".to_owned(),
+        values: "".to_owned(),
+    };
+    release.add_revision(&chart, target.values.clone(), format!("Rollback to {}", target.revision));
+    Ok("Rollback was a success! Happy Helming!\n".to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Args, Chart, execute, State};
+
+    fn helm(state: &mut State, args: &str) -> Result<String, String> {
+        execute(state, Args::parse(args.split_whitespace().map(|a| a.to_owned()).collect()))
+    }
+
+    fn state() -> State {
+        let chart = |version: &str| Chart {
+            name: "nginx".to_owned(),
+            version: version.to_owned(),
+            app_version: "1.25.0".to_owned(),
+            description: "NGINX".to_owned(),
+            readme: "# NGINX".to_owned(),
+            values: "replicaCount: 1\n".to_owned(),
+        };
+        let mut state = State::default();
+        state.catalog.insert("https://charts.example.com".to_owned(), vec![chart("15.0.2"), chart("15.1.0"), chart("9.0.0")]);
+        state
+    }
+
+    #[test]
+    fn should_search_newest_charts_of_added_repositories() {
+        let mut state = state();
+        assert!(helm(&mut state, "repo ls -o json").is_err());
+        assert!(helm(&mut state, "repo add other https://other.example.com").is_err());
+
+        helm(&mut state, "--kube-context kind repo add example https://charts.example.com").unwrap();
+        let charts: serde_json::Value = serde_json::from_str(helm(&mut state, "-o json search repo NGINX").unwrap().as_str()).unwrap();
+
+        assert_eq!(charts[0]["name"], "example/nginx");
+        assert_eq!(charts[0]["version"], "15.1.0");
+        assert!(helm(&mut state, "show chart example/nginx").unwrap().contains("version: 15.1.0"));
+    }
+
+    #[test]
+    fn should_track_release_revisions() {
+        let mut state = state();
+        helm(&mut state, "repo add example https://charts.example.com").unwrap();
+        helm(&mut state, "install web example/nginx --namespace apps --create-namespace --version 15.0.2").unwrap();
+        assert!(helm(&mut state, "install web example/nginx --namespace apps").is_err());
+        helm(&mut state, "upgrade web example/nginx -n apps --reuse-values").unwrap();
+        helm(&mut state, "rollback web -n apps").unwrap();
+
+        let history: serde_json::Value = serde_json::from_str(helm(&mut state, "history web -n apps -o json").unwrap().as_str()).unwrap();
+        assert_eq!(history[2]["revision"], 3);
+        assert_eq!(history[2]["chart"], "nginx-15.0.2");
+        assert_eq!(history[2]["description"], "Rollback to 1");
+        assert_eq!(history[1]["status"], "superseded");
+
+        let releases: serde_json::Value = serde_json::from_str(helm(&mut state, "list -A -o json").unwrap().as_str()).unwrap();
+        assert_eq!(releases[0]["revision"], "3");
+        assert_eq!(helm(&mut state, "get values web -n apps -o yaml").unwrap(), "null\n");
+
+        helm(&mut state, "uninstall web -n apps").unwrap();
+        assert_eq!(helm(&mut state, "list -A -o json").unwrap(), "[]");
+    }
+
+    #[test]
+    fn should_not_rollback_first_revision() {
+        let mut state = state();
+        helm(&mut state, "repo add example https://charts.example.com").unwrap();
+        helm(&mut state, "upgrade --install web example/nginx -n apps --timeout 300s").unwrap();
+
+        assert_eq!(helm(&mut state, "rollback web -n apps"), Err("release has no 0 version".to_owned()));
+        assert_eq!(state.releases[0].revisions.len(), 1);
+    }
+
+    #[test]
+    fn should_search_all_versions() {
+        let mut state = state();
+        helm(&mut state, "repo add example https://charts.example.com").unwrap();
+        let charts: serde_json::Value = serde_json::from_str(helm(&mut state, "-o json search repo nginx --versions").unwrap().as_str()).unwrap();
+
+        let versions = charts.as_array().unwrap().iter().map(|c| c["version"].as_str().unwrap()).collect::<Vec<&str>>();
+        assert_eq!(versions, vec!["15.1.0", "15.0.2", "9.0.0"]);
+    }
+}
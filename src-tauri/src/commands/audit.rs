@@ -0,0 +1,16 @@
+use std::path::Path;
+
+use crate::audit;
+use crate::commands::state::AppState;
+
+#[tauri::command]
+pub async fn query_audit_log(state: tauri::State<'_, AppState>, query: audit::AuditQuery) -> Result<Vec<audit::AuditEntry>, String> {
+    let result = state.audit_log.query(&query).map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn export_audit_log(state: tauri::State<'_, AppState>, query: audit::AuditQuery, path: String) -> Result<(), String> {
+    state.audit_log.export(&query, Path::new(path.as_str())).map_err(|e| e.to_string())?;
+    Ok(())
+}
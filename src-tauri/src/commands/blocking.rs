@@ -0,0 +1,10 @@
+/// Runs helm calls on the blocking thread pool, so they don't stall the async runtime.
+pub async fn run_blocking<T, F>(task: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -1,47 +1,180 @@
+use tauri::Manager;
+
+use crate::commands::blocking::run_blocking;
 use crate::commands::error::error_to_string;
+use crate::commands::operations::{OperationKind, track};
+use crate::commands::state::AppState;
 use crate::helm;
 
 #[tauri::command]
-pub async fn find_charts_in_repo(filter: String) -> Result<Vec<helm::cmd::ChartListItem>, String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .find_charts_in_repo(filter)
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn find_charts_in_repo(app: tauri::AppHandle, state: tauri::State<'_, AppState>, filter: String) -> Result<helm::index::SearchResult, String> {
+    let helm = state.helm();
+    let key = format!("{}/{}", helm.settings().active_profile, filter);
+    run_blocking(move || {
+        let result = app.state::<AppState>().charts
+            .get_or_load(key, || helm.query(|api| api.search_index(filter)))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn get_chart_details(app: tauri::AppHandle, state: tauri::State<'_, AppState>, name: String) -> Result<helm::cmd::ChartDetails, String> {
+    let helm = state.helm();
+    let key = format!("{}/{}", helm.settings().active_profile, name);
+    run_blocking(move || {
+        // local charts are under development, they are read again every time
+        if helm::archive::is_local_chart(name.as_str()) {
+            return helm.query(|api| api.get_chart_details(helm.index(), name))
+                .map_err(error_to_string());
+        }
+        let result = app.state::<AppState>().chart_details
+            .get_or_load(key, || helm.query(|api| api.get_chart_details(helm.index(), name)))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn get_chart_versions(state: tauri::State<'_, AppState>, chart_name: String) -> Result<Vec<helm::cmd::ChartListItem>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.chart_versions(helm.index(), chart_name))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn pull_chart(state: tauri::State<'_, AppState>, chart_name: String, version: Option<String>, destination: String, verify: bool) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.pull_chart(helm.index(), chart_name, version, destination, verify))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn verify_chart(state: tauri::State<'_, AppState>, archive: String) -> Result<helm::verify::VerificationResult, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.verify_chart(archive))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn install_chart(app: tauri::AppHandle, state: tauri::State<'_, AppState>, request: helm::cmd::InstallChartRequest) -> Result<(), String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let subject = format!("{} ({})", request.name, request.chart_name);
+        let result = track(&app, OperationKind::Install, subject, || {
+            helm.mutate(|api| api.install_chart(request))
+        }).map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn template_chart(state: tauri::State<'_, AppState>, request: helm::cmd::InstallChartRequest) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.template_chart(request))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn list_chart_dependencies(state: tauri::State<'_, AppState>, chart_path: String) -> Result<Vec<helm::dependency::DependencyState>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.dependency_list(chart_path))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn update_chart_dependencies(state: tauri::State<'_, AppState>, chart_path: String) -> Result<Vec<helm::dependency::DependencyState>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.dependency_update(chart_path))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn build_chart_dependencies(state: tauri::State<'_, AppState>, chart_path: String) -> Result<Vec<helm::dependency::DependencyState>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.dependency_build(chart_path))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn resolve_subcharts(state: tauri::State<'_, AppState>, chart_path: String, values: Option<String>) -> Result<Vec<helm::dependency::SubchartState>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.resolve_subcharts(chart_path, values))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn lint_chart(state: tauri::State<'_, AppState>, request: helm::lint::LintRequest) -> Result<helm::lint::LintResult, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.lint_chart(request))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
 
 #[tauri::command]
-pub async fn get_chart_details(name: String) -> Result<helm::cmd::ChartDetails, String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .get_chart_details(name)
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn package_chart(state: tauri::State<'_, AppState>, request: helm::package::PackageRequest) -> Result<helm::package::PackageResult, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.package_chart(request))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
 
 #[tauri::command]
-pub async fn install_chart(request: helm::cmd::InstallChartRequest) -> Result<(), String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .install_chart(request)
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn publish_chart(state: tauri::State<'_, AppState>, request: helm::package::PublishRequest) -> Result<helm::package::PublishResult, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.mutate(|api| api.publish_chart(helm.index(), request))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
 
 #[tauri::command]
-pub async fn uninstall_chart(release_name: String, namespace: String) -> Result<(), String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .uninstall_chart(release_name, namespace)
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn uninstall_chart(app: tauri::AppHandle, state: tauri::State<'_, AppState>, release_name: String, namespace: String) -> Result<(), String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let subject = format!("{} ({})", release_name, namespace);
+        let result = track(&app, OperationKind::Uninstall, subject, || {
+            helm.mutate(|api| api.uninstall_chart(release_name, namespace))
+        }).map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
 
 #[tauri::command]
-pub async fn get_values(release_name: String, namespace: String) -> Result<String, String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .get_values(release_name, namespace)
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn get_values(state: tauri::State<'_, AppState>, release_name: String, namespace: String) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.get_values(release_name, namespace))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
@@ -0,0 +1,37 @@
+use crate::commands::blocking::run_blocking;
+use crate::commands::error::error_to_string;
+use crate::commands::operations::{OperationKind, track};
+use crate::commands::state::AppState;
+use crate::helm;
+
+#[tauri::command]
+pub async fn load_helmfile(state: tauri::State<'_, AppState>, path: String) -> Result<helm::helmfile::HelmfilePlan, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.load_helmfile(path))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn apply_helmfile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let subject = format!("Helmfile {}", path);
+        let result = track(&app, OperationKind::Import, subject, || {
+            helm.mutate(|api| api.apply_helmfile(path))
+        }).map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn export_helmfile(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.export_helmfile())
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
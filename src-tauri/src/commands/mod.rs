@@ -2,4 +2,13 @@ pub mod repo;
 pub mod releases;
 pub mod others;
 pub mod charts;
+pub mod helmfile;
+pub mod watcher;
+pub mod operations;
+pub mod audit;
+pub mod setup;
+pub mod settings;
+pub mod profiles;
+pub mod state;
+mod blocking;
 mod error;
\ No newline at end of file
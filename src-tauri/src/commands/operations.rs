@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::state::AppState;
+use crate::helm;
+use crate::settings::NotificationSettings;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum OperationKind {
+    #[serde(rename = "install")]
+    Install,
+    #[serde(rename = "uninstall")]
+    Uninstall,
+    #[serde(rename = "import")]
+    Import,
+    #[serde(rename = "batch")]
+    Batch,
+}
+
+impl OperationKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            OperationKind::Install => "Install",
+            OperationKind::Uninstall => "Uninstall",
+            OperationKind::Import => "Import",
+            OperationKind::Batch => "Batch operation",
+        }
+    }
+}
+
+fn is_enabled(settings: &NotificationSettings, kind: OperationKind, elapsed: Duration) -> bool {
+    let enabled = match kind {
+        OperationKind::Install => settings.install,
+        OperationKind::Uninstall => settings.uninstall,
+        OperationKind::Import => settings.import,
+        OperationKind::Batch => settings.batch,
+    };
+    enabled && elapsed >= Duration::from_secs(settings.minimum_duration_seconds)
+}
+
+/// Runs operation registered as running and notifies about its completion or failure when enabled for its kind.
+pub fn track<T, F>(app: &tauri::AppHandle, kind: OperationKind, subject: String, operation: F) -> helm::api::Result<T>
+    where
+        F: FnOnce() -> helm::api::Result<T>,
+{
+    let state = app.state::<AppState>();
+    let _running = state.operations.start(kind, subject.clone());
+    let started = Instant::now();
+    let result = operation();
+    let elapsed = started.elapsed();
+    match &result {
+        Ok(_) => notify(app, kind, true, elapsed, format!("{} completed in {}", subject, format_duration(elapsed))),
+        Err(e) => notify(app, kind, false, elapsed, format!("{}: {}", subject, e)),
+    }
+    result
+}
+
+/// Shows system notification about finished operation when enabled for its kind and it took long enough.
+pub fn notify(app: &tauri::AppHandle, kind: OperationKind, succeeded: bool, elapsed: Duration, message: String) {
+    if !is_enabled(&app.state::<AppState>().settings.get().notifications, kind, elapsed) {
+        return;
+    }
+
+    let title = match succeeded {
+        true => format!("{} finished", kind.describe()),
+        false => format!("{} failed", kind.describe()),
+    };
+    let _ = tauri::api::notification::Notification::new(&app.config().tauri.bundle.identifier)
+        .title(title)
+        .body(message)
+        .show();
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        _ => format!("{}m {}s", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::commands::operations::{format_duration, is_enabled, OperationKind};
+    use crate::settings::NotificationSettings;
+
+    #[test]
+    fn should_notify_only_enabled_long_running_operations() {
+        let settings = NotificationSettings { uninstall: false, minimum_duration_seconds: 10, ..NotificationSettings::default() };
+
+        assert!(is_enabled(&settings, OperationKind::Install, Duration::from_secs(10)));
+        assert!(!is_enabled(&settings, OperationKind::Install, Duration::from_millis(9_999)));
+        assert!(!is_enabled(&settings, OperationKind::Uninstall, Duration::from_secs(60)));
+        assert!(is_enabled(&settings, OperationKind::Batch, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn should_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(800)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m 0s");
+        assert_eq!(format_duration(Duration::from_secs(754)), "12m 34s");
+    }
+}
@@ -1,11 +1,47 @@
+use std::collections::BTreeMap;
+
+use crate::commands::blocking::run_blocking;
 use crate::commands::error::error_to_string;
-use crate::helm;
+use crate::commands::state::{AppState, RunningOperation};
+use crate::logging;
+
+const DEFAULT_RECENT_LOGS_LIMIT: usize = 200;
+
+#[tauri::command]
+pub async fn version(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.version())
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn helm_env(state: tauri::State<'_, AppState>) -> Result<BTreeMap<String, String>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.env())
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn running_operations(state: tauri::State<'_, AppState>) -> Result<Vec<RunningOperation>, String> {
+    Ok(state.operations.running())
+}
+
+#[tauri::command]
+pub async fn cancel_operation(state: tauri::State<'_, AppState>, id: u64) -> Result<bool, String> {
+    Ok(state.operations.cancel(id))
+}
 
 #[tauri::command]
-pub async fn version() -> Result<String, String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .version()
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn recent_logs(state: tauri::State<'_, AppState>, limit: Option<usize>, level: Option<String>) -> Result<Vec<logging::LogLine>, String> {
+    let level = match level {
+        Some(level) => level.parse::<log::Level>().map_err(|e| e.to_string())?,
+        None => log::Level::Trace
+    };
+    Ok(state.logs.get(limit.unwrap_or(DEFAULT_RECENT_LOGS_LIMIT), level))
 }
@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use tauri::Manager;
+
+use crate::commands::state::AppState;
+use crate::profile::Profile;
+use crate::settings::Settings;
+
+const PROFILES_DIR: &'static str = "profiles";
+
+fn profiles_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path_resolver()
+        .app_data_dir()
+        .ok_or("Cannot resolve application data directory".to_owned())?;
+    Ok(data_dir.join(PROFILES_DIR))
+}
+
+/// Creates profile, `isolated` one gets its own helm config, cache and data directories.
+#[tauri::command]
+pub async fn create_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, name: String, isolated: bool) -> Result<Settings, String> {
+    let helm_home = match isolated {
+        true => {
+            let helm_home = profiles_dir(&app)?.join(uuid::Uuid::new_v4().to_string());
+            Some(helm_home.to_string_lossy().to_string())
+        }
+        false => None
+    };
+    let profile = Profile { helm_home, ..Profile::new(name) };
+    let result = state.settings.modify(|s| s.add_profile(profile)).map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn update_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, profile: Profile) -> Result<Settings, String> {
+    let result = state.settings.modify(|s| s.update_profile(profile)).map_err(|e| e.to_string())?;
+    state.invalidate_charts();
+    state.watchers.restart_all(&app, &result);
+    Ok(result)
+}
+
+/// Removes profile together with helm directories created for isolated profile, helm home chosen by user is kept.
+#[tauri::command]
+pub async fn delete_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, name: String) -> Result<Settings, String> {
+    let helm_home = state.settings.get().profiles.into_iter()
+        .find(|p| p.name == name)
+        .and_then(|p| p.helm_home);
+    let result = state.settings.modify(|s| s.remove_profile(name.as_str())).map_err(|e| e.to_string())?;
+
+    let profiles_dir = profiles_dir(&app)?;
+    let created_by_app = |helm_home: &String| {
+        let path = Path::new(helm_home);
+        path.starts_with(&profiles_dir) && !path.components().any(|c| c == Component::ParentDir) && path.exists()
+    };
+    if let Some(helm_home) = helm_home.filter(created_by_app) {
+        if let Err(e) = fs::remove_dir_all(&helm_home) {
+            log::warn!("Cannot remove helm home {} of deleted profile {}: {}", helm_home, name, e);
+        }
+    }
+    Ok(result)
+}
+
+/// Makes profile active for all following commands and notifies windows with `profile-switched` event.
+#[tauri::command]
+pub async fn switch_profile(app: tauri::AppHandle, state: tauri::State<'_, AppState>, name: String) -> Result<Settings, String> {
+    let result = state.settings.modify(|s| s.switch_profile(name.as_str())).map_err(|e| e.to_string())?;
+    state.invalidate_charts();
+    state.watchers.restart_all(&app, &result);
+    let _ = app.emit_all("profile-switched", result.profile());
+    Ok(result)
+}
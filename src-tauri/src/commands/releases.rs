@@ -1,11 +1,89 @@
+use std::time::Instant;
+
+use tauri::Manager;
+
+use crate::commands::blocking::run_blocking;
 use crate::commands::error::error_to_string;
+use crate::commands::operations::{format_duration, notify, OperationKind, track};
+use crate::commands::state::AppState;
 use crate::helm;
 
 #[tauri::command]
-pub async fn list_releases() -> Result<Vec<helm::cmd::Release>, String> {
-    let mut executor = Box::new(crate::executor::DefaultExecutor::new());
-    let result = helm::Api::new(executor.as_mut())
-        .list()
-        .map_err(error_to_string())?;
-    Ok(result)
+pub async fn list_releases(state: tauri::State<'_, AppState>) -> Result<Vec<helm::cmd::Release>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.list())
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn export_release(state: tauri::State<'_, AppState>, release_name: String, namespace: String) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.export_release(release_name, namespace))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn import_release(app: tauri::AppHandle, state: tauri::State<'_, AppState>, bundle: String) -> Result<(), String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = track(&app, OperationKind::Import, "Release bundle".to_owned(), || {
+            helm.mutate(|api| api.import_release(bundle))
+        }).map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn export_gitops(state: tauri::State<'_, AppState>, release_name: String, namespace: String, format: helm::gitops::GitOpsFormat, directory: Option<String>) -> Result<String, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.export_gitops(release_name, namespace, format, directory))
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
+}
+
+#[tauri::command]
+pub async fn batch_operation(app: tauri::AppHandle, window: tauri::Window, state: tauri::State<'_, AppState>, targets: Vec<helm::batch::BatchTarget>, action: helm::batch::BatchAction, concurrency: Option<usize>) -> Result<Vec<helm::batch::BatchResult>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let state = app.state::<AppState>();
+        let running = state.operations.start(OperationKind::Batch, format!("{} releases", targets.len()));
+        let started = Instant::now();
+        let results = helm::batch::run(
+            targets,
+            action,
+            concurrency.unwrap_or(helm::batch::DEFAULT_CONCURRENCY),
+            running.cancelled(),
+            |api| helm.settings().configure(api),
+            || helm.audited_executor(),
+            |result| {
+                let _ = window.emit("batch-result", result.clone());
+            },
+        );
+
+        let failed = results.iter().filter(|r| !r.success).count();
+        let message = match failed {
+            0 => format!("{} releases completed in {}", results.len(), format_duration(started.elapsed())),
+            _ => format!("{} of {} releases failed", failed, results.len())
+        };
+        notify(&app, OperationKind::Batch, failed == 0, started.elapsed(), message);
+        Ok(results)
+    }).await
+}
+
+#[tauri::command]
+pub async fn check_release_updates(state: tauri::State<'_, AppState>) -> Result<Vec<helm::outdated::ReleaseUpdate>, String> {
+    let helm = state.helm();
+    run_blocking(move || {
+        let result = helm.query(|api| api.check_updates())
+            .map_err(error_to_string())?;
+        Ok(result)
+    }).await
 }
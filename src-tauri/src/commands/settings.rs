@@ -0,0 +1,15 @@
+use crate::commands::state::AppState;
+use crate::settings::Settings;
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, AppState>) -> Result<Settings, String> {
+    Ok(state.settings.get())
+}
+
+#[tauri::command]
+pub async fn update_settings(app: tauri::AppHandle, state: tauri::State<'_, AppState>, settings: Settings) -> Result<Settings, String> {
+    let result = state.settings.update(settings).map_err(|e| e.to_string())?;
+    state.invalidate_charts();
+    state.watchers.restart_all(&app, &result);
+    Ok(result)
+}
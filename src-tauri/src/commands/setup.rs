@@ -0,0 +1,47 @@
+use crate::commands::blocking::run_blocking;
+use crate::commands::state::AppState;
+use crate::helm;
+
+#[tauri::command]
+pub async fn discover_helm_binaries(state: tauri::State<'_, AppState>) -> Result<Vec<helm::discovery::HelmInstallation>, String> {
+    let settings = state.settings.get();
+    run_blocking(move || {
+        let mut executor = Box::new(settings.executor());
+        let result = helm::discovery::discover_candidates()
+            .into_iter()
+            .map(|path| helm::discovery::inspect(executor.as_mut(), path.to_string_lossy().as_ref()))
+            .collect();
+        Ok(result)
+    }).await
+}
+
+/// Stores helm binary in settings when it is supported, `None` switches back to helm from PATH.
+#[tauri::command]
+pub async fn set_helm_binary(app: tauri::AppHandle, state: tauri::State<'_, AppState>, path: Option<String>) -> Result<helm::discovery::SetupStatus, String> {
+    let settings = state.settings.get();
+    let path = path.filter(|p| !p.trim().is_empty());
+    let binary = path.clone().unwrap_or(helm::cmd::DEFAULT_BINARY.to_owned());
+    let installation = run_blocking(move || {
+        let mut executor = Box::new(settings.executor());
+        Ok(helm::discovery::inspect(executor.as_mut(), binary.as_str()))
+    }).await?;
+    if installation.supported {
+        let result = state.settings.modify(|s| {
+            s.helm_binary = path;
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        state.invalidate_charts();
+        state.watchers.restart_all(&app, &result);
+    }
+    Ok(helm::discovery::setup_status(installation))
+}
+
+#[tauri::command]
+pub async fn check_helm_setup(state: tauri::State<'_, AppState>) -> Result<helm::discovery::SetupStatus, String> {
+    let settings = state.settings.get();
+    run_blocking(move || {
+        let mut executor = Box::new(settings.executor());
+        let binary = settings.helm_binary.unwrap_or(helm::cmd::DEFAULT_BINARY.to_owned());
+        Ok(helm::discovery::setup_status(helm::discovery::inspect(executor.as_mut(), binary.as_str())))
+    }).await
+}
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::audit;
+use crate::commands::operations::OperationKind;
+use crate::commands::watcher::ReleaseWatchers;
+use crate::executor;
+use crate::helm;
+use crate::logging;
+use crate::settings::{Settings, SettingsStore};
+
+/// Everything commands share, managed by Tauri.
+pub struct AppState {
+    pub settings: SettingsStore,
+    pub audit_log: audit::AuditLog,
+    pub logs: logging::RecentLogs,
+    pub watchers: ReleaseWatchers,
+    pub operations: OperationRegistry,
+    pub charts: Cache<helm::index::SearchResult>,
+    pub chart_details: Cache<helm::cmd::ChartDetails>,
+    pub index: helm::index::IndexClient,
+}
+
+impl AppState {
+    pub fn new(settings: SettingsStore, audit_log: audit::AuditLog, logs: logging::RecentLogs, index: helm::index::IndexClient) -> Self {
+        AppState {
+            settings,
+            audit_log,
+            logs,
+            watchers: ReleaseWatchers::default(),
+            operations: OperationRegistry::default(),
+            charts: Cache::default(),
+            chart_details: Cache::default(),
+            index,
+        }
+    }
+
+    /// Helm service configured with current settings.
+    pub fn helm(&self) -> HelmService {
+        let settings = self.settings.get();
+        HelmService {
+            index: self.index.clone().with_timeout(settings.command_timeout_seconds.map(Duration::from_secs)),
+            settings,
+            audit_log: self.audit_log.clone(),
+        }
+    }
+
+    /// Cached chart data refer to repositories of the active profile, they are stale after repositories
+    /// or any profile configuration change.
+    pub fn invalidate_charts(&self) {
+        self.charts.clear();
+        self.chart_details.clear();
+    }
+}
+
+/// Creates executors and helm API for a single command, can be moved to a worker thread.
+#[derive(Clone)]
+pub struct HelmService {
+    settings: Settings,
+    audit_log: audit::AuditLog,
+    index: helm::index::IndexClient,
+}
+
+impl HelmService {
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Client reading chart repositories directly, with the command timeout.
+    pub fn index(&self) -> &helm::index::IndexClient {
+        &self.index
+    }
+
+    /// Runs helm calls which only read cluster or repositories.
+    pub fn query<T, F>(&self, call: F) -> helm::api::Result<T>
+        where
+            F: FnOnce(&mut helm::Api) -> helm::api::Result<T>,
+    {
+        let mut executor = self.settings.executor();
+        call(&mut self.settings.api(&mut executor))
+    }
+
+    /// Runs helm calls changing cluster or repositories, they are recorded in the audit log.
+    pub fn mutate<T, F>(&self, call: F) -> helm::api::Result<T>
+        where
+            F: FnOnce(&mut helm::Api) -> helm::api::Result<T>,
+    {
+        let mut executor = self.audited_executor();
+        call(&mut self.settings.api(executor.as_mut()).with_audit_log(Some(self.audit_log.clone())))
+    }
+
+    pub fn audited_executor(&self) -> Box<dyn executor::Executor> {
+        Box::new(audit::AuditingExecutor::new(Box::new(self.settings.executor()), self.audit_log.clone()))
+    }
+}
+
+/// Values cached by key until cleared.
+pub struct Cache<V> {
+    entries: Mutex<HashMap<String, V>>,
+}
+
+impl<V> Default for Cache<V> {
+    fn default() -> Self {
+        Cache {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<V: Clone> Cache<V> {
+    /// Cached value or result of `load`, errors are not cached.
+    pub fn get_or_load<E, F>(&self, key: String, load: F) -> Result<V, E>
+        where
+            F: FnOnce() -> Result<V, E>,
+    {
+        if let Some(value) = self.entries.lock().ok().and_then(|e| e.get(&key).cloned()) {
+            return Ok(value);
+        }
+        let value = load()?;
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, value.clone());
+        }
+        Ok(value)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RunningOperation {
+    pub id: u64,
+    pub kind: OperationKind,
+    pub subject: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip)]
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Long-running operations in progress.
+#[derive(Default)]
+pub struct OperationRegistry {
+    next_id: AtomicU64,
+    running: Mutex<BTreeMap<u64, RunningOperation>>,
+}
+
+impl OperationRegistry {
+    /// Registers operation until returned guard is dropped.
+    pub fn start(&self, kind: OperationKind, subject: String) -> RunningOperationGuard<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(id, RunningOperation { id, kind, subject, started_at: chrono::Utc::now(), cancelled: cancelled.clone() });
+        }
+        RunningOperationGuard { registry: self, id, cancelled }
+    }
+
+    /// Asks operation to stop, returns false when no such operation is running.
+    pub fn cancel(&self, id: u64) -> bool {
+        let Ok(running) = self.running.lock() else {
+            return false;
+        };
+        match running.get(&id) {
+            Some(operation) => {
+                operation.cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn running(&self) -> Vec<RunningOperation> {
+        self.running.lock()
+            .map(|r| r.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub struct RunningOperationGuard<'a> {
+    registry: &'a OperationRegistry,
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RunningOperationGuard<'_> {
+    /// Flag set when operation is cancelled, long-running operations should check it between steps.
+    pub fn cancelled(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+}
+
+impl Drop for RunningOperationGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.registry.running.lock() {
+            running.remove(&self.id);
+        }
+    }
+}
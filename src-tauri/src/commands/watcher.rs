@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::commands::state::AppState;
+use crate::helm;
+use crate::settings::Settings;
+
+/// Running release watchers by kube context, `None` is the current context.
+#[derive(Default)]
+pub struct ReleaseWatchers {
+    running: Mutex<HashMap<Option<String>, Watcher>>,
+}
+
+struct Watcher {
+    stop: Arc<AtomicBool>,
+    interval_seconds: Option<u64>,
+}
+
+impl ReleaseWatchers {
+    /// Restarts running watchers with given settings, they would keep watching clusters of the previous profile.
+    pub fn restart_all(&self, app: &tauri::AppHandle, settings: &Settings) {
+        let Ok(mut running) = self.running.lock() else {
+            return;
+        };
+        for (kube_context, watcher) in running.iter_mut() {
+            watcher.stop.store(true, Ordering::Relaxed);
+            watcher.stop = spawn_watcher(app.clone(), settings.clone(), kube_context.clone(), watcher.interval_seconds);
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct ReleaseChangeEvent {
+    #[serde(rename = "kubeContext")]
+    kube_context: Option<String>,
+    change: helm::watcher::ReleaseChange,
+}
+
+#[derive(Serialize, Clone)]
+struct ReleaseWatcherErrorEvent {
+    #[serde(rename = "kubeContext")]
+    kube_context: Option<String>,
+    message: String,
+}
+
+#[tauri::command]
+pub async fn start_release_watcher(app: tauri::AppHandle, state: tauri::State<'_, AppState>, kube_context: Option<String>, interval_seconds: Option<u64>) -> Result<(), String> {
+    let mut running = state.watchers.running.lock().map_err(|e| e.to_string())?;
+    if let Some(watcher) = running.remove(&kube_context) {
+        watcher.stop.store(true, Ordering::Relaxed);
+    }
+
+    let stop = spawn_watcher(app, state.settings.get(), kube_context.clone(), interval_seconds);
+    running.insert(kube_context, Watcher { stop, interval_seconds });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_release_watcher(state: tauri::State<'_, AppState>, kube_context: Option<String>) -> Result<(), String> {
+    let mut running = state.watchers.running.lock().map_err(|e| e.to_string())?;
+    if let Some(watcher) = running.remove(&kube_context) {
+        watcher.stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Watches releases on a background thread until returned flag is set.
+fn spawn_watcher(app: tauri::AppHandle, settings: Settings, kube_context: Option<String>, interval_seconds: Option<u64>) -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let watcher_stop = stop.clone();
+    let interval = Duration::from_secs(interval_seconds.unwrap_or(settings.refresh_interval_seconds).max(1));
+    thread::spawn(move || {
+        let mut executor = Box::new(settings.executor());
+        let change_app = app.clone();
+        let change_context = kube_context.clone();
+        helm::watcher::watch(
+            executor.as_mut(),
+            settings.helm_binary.clone(),
+            kube_context.clone().or(settings.profile().kube_context),
+            interval,
+            watcher_stop,
+            move |change| {
+                let _ = change_app.emit_all("release-changed", ReleaseChangeEvent {
+                    kube_context: change_context.clone(),
+                    change,
+                });
+            },
+            move |message| {
+                let _ = app.emit_all("release-watcher-error", ReleaseWatcherErrorEvent {
+                    kube_context: kube_context.clone(),
+                    message,
+                });
+            },
+        );
+    });
+    stop
+}
@@ -1,11 +1,23 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug};
-use std::process::Command;
+use std::io::Read;
+use std::process::{Child, Command, Output, Stdio};
 use std::str::Utf8Error;
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::redaction::{redact_args, redact_text};
 
 #[derive(Debug)]
 pub enum Error {
     NoCommand,
     CannotExecuteCommand(String),
+    /// Command exited with failure, some commands still print results, e.g. `helm lint` findings.
+    CommandFailed {
+        stdout: String,
+        stderr: String,
+    },
 }
 
 
@@ -24,16 +36,30 @@ impl From<std::io::Error> for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Number of running commands by kube context key, shared by all executors.
+static RUNNING: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+static FINISHED: Condvar = Condvar::new();
+
 
 pub trait Executor {
     fn command(&mut self, cmd: String) -> &mut dyn Executor;
     fn arg(&mut self, name: String) -> &mut dyn Executor;
+    /// Environment variable set for all following executions.
+    fn env(&mut self, name: String, value: String) -> &mut dyn Executor;
+    /// Working directory for all following executions.
+    fn current_dir(&mut self, dir: String) -> &mut dyn Executor;
     fn execute(&mut self) -> Result<String>;
 }
 
 pub struct DefaultExecutor {
     command: String,
     args: Vec<String>,
+    envs: BTreeMap<String, String>,
+    current_dir: Option<String>,
+    timeout: Option<Duration>,
+    limit_per_context: Option<usize>,
 }
 
 impl DefaultExecutor {
@@ -41,8 +67,35 @@ impl DefaultExecutor {
         DefaultExecutor {
             command: "".to_owned(),
             args: vec![],
+            envs: BTreeMap::new(),
+            current_dir: None,
+            timeout: None,
+            limit_per_context: None,
         }
     }
+
+    /// Waits before executing while `limit` commands already run against the same kube context.
+    pub fn with_limit_per_context(mut self, limit: Option<usize>) -> Self {
+        self.limit_per_context = limit.map(|l| l.max(1));
+        self
+    }
+
+    /// Kubeconfig and `--kube-context` identify cluster the command talks to.
+    fn context_key(&self) -> String {
+        let kube_context = self.args.iter()
+            .position(|a| a == "--kube-context")
+            .and_then(|i| self.args.get(i + 1))
+            .map(|c| c.as_str())
+            .unwrap_or("");
+        let kubeconfig = self.envs.get("KUBECONFIG").map(|c| c.as_str()).unwrap_or("");
+        format!("{}|{}", kubeconfig, kube_context)
+    }
+
+    /// Kills commands running longer than `timeout`, except ones passing helm `--timeout` which stop by themselves.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
 }
 
 impl Executor for DefaultExecutor {
@@ -54,22 +107,32 @@ impl Executor for DefaultExecutor {
         self.args.push(name);
         self
     }
+    fn env(&mut self, name: String, value: String) -> &mut dyn Executor {
+        self.envs.insert(name, value);
+        self
+    }
+    fn current_dir(&mut self, dir: String) -> &mut dyn Executor {
+        self.current_dir = Some(dir);
+        self
+    }
 
     fn execute(&mut self) -> Result<String> {
         if self.command.is_empty() {
             return Err(Error::NoCommand);
         }
-        // TODO: make it in debug
-        print!("Executed command: {}", self.command.clone());
-        for arg in self.args.iter() {
-            print!(" {}", arg)
-        }
-        println!();
+        let _permit = self.limit_per_context.map(|limit| Permit::acquire(self.context_key(), limit));
+        // killed install or upgrade leaves release pending, helm has to give up on its own
+        let timeout = self.timeout.filter(|_| !self.args.iter().any(|a| a == "--timeout"));
+        log::debug!("Executed command: {} {}", self.command, redact_args(&self.args).join(" "));
 
         let mut cmd = Command::new(self.command.clone());
         for arg in self.args.iter() {
             cmd.arg(arg.clone());
         }
+        cmd.envs(self.envs.iter());
+        if let Some(dir) = self.current_dir.as_ref() {
+            cmd.current_dir(dir);
+        }
 
         self.args.clear();
 
@@ -79,10 +142,167 @@ impl Executor for DefaultExecutor {
                 .to_string()
         };
 
-        let output = cmd.output()?;
+        let output = match timeout {
+            Some(timeout) => wait_with_timeout(cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?, timeout)?,
+            None => cmd.output()?
+        };
         match output.status.success() {
             true => Ok(to_result(output.stdout)),
-            false => Err(Error::CannotExecuteCommand(to_result(output.stderr))),
+            false => {
+                let stderr = to_result(output.stderr);
+                log::warn!("Command {} failed with {}: {}", self.command, output.status, redact_text(stderr.trim()));
+                Err(Error::CommandFailed { stdout: to_result(output.stdout), stderr })
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Slot of a running command, released on drop.
+struct Permit {
+    key: String,
+}
+
+impl Permit {
+    fn acquire(key: String, limit: usize) -> Self {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        while running.get(&key).copied().unwrap_or(0) >= limit {
+            running = FINISHED.wait(running).unwrap_or_else(|e| e.into_inner());
+        }
+        *running.entry(key.clone()).or_insert(0) += 1;
+        Permit { key }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = running.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                running.remove(&self.key);
+            }
+        }
+        FINISHED.notify_all();
+    }
+}
+
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Result<Output> {
+    let started = Instant::now();
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(Error::CannotExecuteCommand(format!("Command timed out after {}s", timeout.as_secs())));
+        }
+        thread::sleep(TIMEOUT_CHECK_INTERVAL);
+    };
+
+    let collect = |output: Option<JoinHandle<Vec<u8>>>| output
+        .map(|o| o.join().unwrap_or_default())
+        .unwrap_or_default();
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut reader: R) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = vec![];
+        let _ = reader.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::executor::{DefaultExecutor, Executor};
+
+    #[cfg(unix)]
+    #[test]
+    fn should_kill_command_after_timeout() {
+        let mut executor = DefaultExecutor::new().with_timeout(Some(Duration::from_millis(200)));
+        let started = Instant::now();
+
+        let slow = executor.command("sleep".to_owned()).arg("5".to_owned()).execute();
+        let fast = executor.command("echo".to_owned()).arg("done".to_owned()).execute();
+
+        assert!(slow.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(fast.unwrap().trim(), "done");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_leave_commands_with_own_timeout_running() {
+        let mut executor = DefaultExecutor::new().with_timeout(Some(Duration::from_millis(100)));
+
+        let result = executor.command("sh".to_owned())
+            .arg("-c".to_owned())
+            .arg("sleep 0.3 && echo deployed".to_owned())
+            .arg("--timeout".to_owned())
+            .execute();
+
+        assert_eq!(result.unwrap().trim(), "deployed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_pass_environment_and_working_directory() {
+        let mut executor = DefaultExecutor::new();
+        executor.env("HELM_DRIVER".to_owned(), "sql".to_owned())
+            .current_dir("/".to_owned());
+
+        let first = executor.command("sh".to_owned()).arg("-c".to_owned()).arg("echo $HELM_DRIVER $(pwd)".to_owned()).execute();
+        let second = executor.command("sh".to_owned()).arg("-c".to_owned()).arg("echo $HELM_DRIVER".to_owned()).execute();
+
+        assert_eq!(first.unwrap().trim(), "sql /");
+        assert_eq!(second.unwrap().trim(), "sql");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_limit_concurrent_commands_per_context() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        let marker = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let run = |context: &'static str, script: String| thread::spawn(move || {
+            let mut executor = DefaultExecutor::new().with_limit_per_context(Some(1));
+            executor.command("sh".to_owned())
+                .arg("-c".to_owned())
+                .arg(script)
+                .arg("--kube-context".to_owned())
+                .arg(context.to_owned())
+                .execute()
+        });
+
+        // holds the only slot of its context until released
+        let first = run("limited", format!("touch {}; while [ ! -e {} ]; do sleep 0.01; done", marker("started"), marker("released")));
+        while !dir.join("started").exists() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // succeeds only when it runs after the first command was released
+        let second = run("limited", format!("test -e {}", marker("released")));
+        let other = run("other", "true".to_owned());
+
+        let other = other.join().unwrap();
+        std::fs::write(dir.join("released"), "").unwrap();
+        let first = first.join().unwrap();
+        let second = second.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(other.is_ok());
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+    }
+}
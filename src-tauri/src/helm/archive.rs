@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::helm::cmd::{ChartDetails, ChartInfo, render_readme};
+
+#[derive(Debug)]
+pub enum Error {
+    CannotRead(String),
+    InvalidChart(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::CannotRead(value.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::InvalidChart(format!("Invalid Chart.yaml: {}", value))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Chart read from archive or directory without helm.
+#[derive(Serialize, Debug, Clone)]
+pub struct ChartContents {
+    pub info: ChartInfo,
+    /// README as markdown.
+    pub readme: Option<String>,
+    pub values: String,
+    #[serde(rename = "valuesSchema")]
+    pub values_schema: Option<String>,
+    /// Paths relative to chart root, e.g. `templates/deployment.yaml`.
+    pub templates: Vec<String>,
+    pub crds: Vec<String>,
+    pub subcharts: Vec<ChartContents>,
+}
+
+impl From<ChartContents> for ChartDetails {
+    fn from(value: ChartContents) -> Self {
+        ChartDetails {
+            readme: render_readme(value.readme.unwrap_or_default().as_str()),
+            values: value.values,
+            values_schema: value.values_schema,
+            templates: value.templates,
+            crds: value.crds,
+            subcharts: value.subcharts.into_iter().map(|s| s.info).collect(),
+            info: value.info,
+        }
+    }
+}
+
+/// Chart reference points to local directory or archive instead of repository, helm resolves it the same way.
+pub fn is_local_chart(reference: &str) -> bool {
+    Path::new(reference).exists()
+}
+
+impl ChartContents {
+    /// Dependencies declared in `Chart.yaml` without subchart in `charts/`.
+    pub fn missing_dependencies(&self) -> Vec<String> {
+        self.info.dependencies.iter()
+            .flatten()
+            .filter(|d| !self.subcharts.iter().any(|s| s.info.name == d.name))
+            .map(|d| d.name.clone())
+            .collect()
+    }
+}
+
+/// Reads chart from packaged `.tgz` archive or unpacked chart directory.
+pub fn read(path: &Path) -> Result<ChartContents> {
+    match path.is_dir() {
+        true => {
+            let mut files = BTreeMap::new();
+            read_directory(path, "", &mut files)?;
+            contents(files)
+        }
+        false => read_archive(fs::read(path)?.as_slice()),
+    }
+}
+
+/// Reads chart from `.tgz` archive content.
+pub fn read_archive(archive: &[u8]) -> Result<ChartContents> {
+    let mut files = BTreeMap::new();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        // archives hold a single top level directory named after the chart
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let Some((_, path)) = path.split_once('/') else {
+            continue;
+        };
+        let path = path.to_owned();
+        let mut content = vec![];
+        entry.read_to_end(&mut content)?;
+        files.insert(path, content);
+    }
+    contents(files)
+}
+
+/// Collects files with paths relative to chart root, hidden files and directories are skipped like by `.helmignore` defaults.
+fn read_directory(dir: &Path, prefix: &str, files: &mut BTreeMap<String, Vec<u8>>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        match entry.file_type()?.is_dir() {
+            true => read_directory(&entry.path(), format!("{}/", path).as_str(), files)?,
+            false => { files.insert(path, fs::read(entry.path())?); }
+        }
+    }
+    Ok(())
+}
+
+fn contents(files: BTreeMap<String, Vec<u8>>) -> Result<ChartContents> {
+    let text = |name: &str| files.get(name).map(|c| String::from_utf8_lossy(c).to_string());
+    let info = text("Chart.yaml").ok_or(Error::InvalidChart("Chart.yaml is missing".to_owned()))?;
+    let info: ChartInfo = serde_yaml::from_str(info.as_str())?;
+
+    let readme = files.keys()
+        .find(|p| ["readme.md", "readme.txt", "readme"].contains(&p.to_lowercase().as_str()))
+        .and_then(|p| text(p));
+    let in_dir = |dir: &str| files.keys()
+        .filter(|p| p.starts_with(dir))
+        .cloned()
+        .collect::<Vec<String>>();
+
+    let mut subcharts = vec![];
+    let mut unpacked: BTreeMap<String, BTreeMap<String, Vec<u8>>> = BTreeMap::new();
+    for (path, content) in files.iter() {
+        let Some(path) = path.strip_prefix("charts/") else {
+            continue;
+        };
+        match path.split_once('/') {
+            Some((name, file)) => { unpacked.entry(name.to_owned()).or_default().insert(file.to_owned(), content.clone()); }
+            None if path.ends_with(".tgz") => subcharts.push(read_archive(content)?),
+            None => {}
+        }
+    }
+    for (name, files) in unpacked {
+        subcharts.push(contents(files).map_err(|e| match e {
+            Error::InvalidChart(e) => Error::InvalidChart(format!("Subchart {}: {}", name, e)),
+            e => e,
+        })?);
+    }
+
+    Ok(ChartContents {
+        info,
+        readme,
+        values: text("values.yaml").unwrap_or_default(),
+        values_schema: text("values.schema.json"),
+        templates: in_dir("templates/"),
+        crds: in_dir("crds/"),
+        subcharts,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use crate::helm::archive::{read, read_archive};
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn should_read_packaged_chart_with_subcharts() {
+        let common = archive(&[("common/Chart.yaml", b"apiVersion: v2\nname: common\nversion: 2.6.0\ntype: library\n")]);
+        let chart = archive(&[
+            ("nginx/Chart.yaml", b"apiVersion: v2\nname: nginx\nversion: 15.1.0\nappVersion: 1.25.1\n"),
+            ("nginx/README.md", b"# NGINX\n"),
+            ("nginx/values.yaml", b"replicaCount: 1\n"),
+            ("nginx/values.schema.json", b"{\"type\": \"object\"}"),
+            ("nginx/templates/deployment.yaml", b"kind: Deployment\n"),
+            ("nginx/templates/_helpers.tpl", b""),
+            ("nginx/crds/server.yaml", b"kind: CustomResourceDefinition\n"),
+            ("nginx/charts/common-2.6.0.tgz", common.as_slice()),
+        ]);
+
+        let contents = read_archive(chart.as_slice()).unwrap();
+
+        assert_eq!(contents.info.name, "nginx");
+        assert_eq!(contents.readme, Some("# NGINX\n".to_owned()));
+        assert_eq!(contents.values, "replicaCount: 1\n");
+        assert_eq!(contents.values_schema, Some("{\"type\": \"object\"}".to_owned()));
+        assert_eq!(contents.templates, vec!["templates/_helpers.tpl", "templates/deployment.yaml"]);
+        assert_eq!(contents.crds, vec!["crds/server.yaml"]);
+        assert_eq!(contents.subcharts[0].info.chart_type, Some("library".to_owned()));
+    }
+
+    #[test]
+    fn should_read_chart_directory() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(dir.join("templates")).unwrap();
+        fs::create_dir_all(dir.join("charts/postgresql")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::write(dir.join("Chart.yaml"), "apiVersion: v2\nname: app\nversion: 0.1.0-dev\n").unwrap();
+        fs::write(dir.join("templates/service.yaml"), "kind: Service\n").unwrap();
+        fs::write(dir.join("charts/postgresql/Chart.yaml"), "apiVersion: v2\nname: postgresql\nversion: 12.5.8\n").unwrap();
+        fs::write(dir.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let contents = read(&dir).unwrap();
+        fs::remove_file(dir.join("Chart.yaml")).unwrap();
+        let missing = read(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(contents.info.version, "0.1.0-dev");
+        assert_eq!(contents.readme, None);
+        assert_eq!(contents.values, "");
+        assert_eq!(contents.templates, vec!["templates/service.yaml"]);
+        assert_eq!(contents.subcharts[0].info.name, "postgresql");
+        assert!(contents.missing_dependencies().is_empty());
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::executor::Executor;
+use crate::helm;
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchTarget {
+    pub release: String,
+    pub namespace: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BatchAction {
+    #[serde(rename = "uninstall")]
+    Uninstall,
+    #[serde(rename = "upgradeLatest")]
+    UpgradeLatest,
+    #[serde(rename = "rollback")]
+    Rollback,
+    #[serde(rename = "refreshValues")]
+    RefreshValues,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchResult {
+    pub target: BatchTarget,
+    pub success: bool,
+    /// Error message on failure, current values for [BatchAction::RefreshValues].
+    pub message: Option<String>,
+}
+
+/// Executes action for every target using at most `concurrency` helm processes at once.
+///
+/// Every worker gets its own executor from `new_executor` and API prepared by `configure`,
+/// `on_result` is called as soon as a target is done, results are returned in the same order as targets.
+/// Once `cancelled` is set, targets not started yet are reported as failed without running the action.
+pub fn run<A, F, C>(targets: Vec<BatchTarget>, action: BatchAction, concurrency: usize, cancelled: &AtomicBool, configure: A, new_executor: F, on_result: C) -> Vec<BatchResult>
+    where
+        A: Fn(helm::Api) -> helm::Api + Sync,
+        F: Fn() -> Box<dyn Executor> + Sync,
+        C: Fn(&BatchResult) + Sync,
+{
+    let count = targets.len();
+    let queue = Mutex::new(targets.into_iter().enumerate().collect::<VecDeque<(usize, BatchTarget)>>());
+    let results: Mutex<Vec<Option<BatchResult>>> = Mutex::new(vec![None; count]);
+    let workers = concurrency.max(1).min(count);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                let mut executor = new_executor();
+                loop {
+                    let next = queue.lock().expect("batch queue poisoned").pop_front();
+                    let Some((index, target)) = next else {
+                        break;
+                    };
+
+                    let result = if cancelled.load(Ordering::Relaxed) {
+                        BatchResult { target, success: false, message: Some("Cancelled".to_owned()) }
+                    } else {
+                        let mut api = configure(helm::Api::new(executor.as_mut()));
+                        match execute(&mut api, &target, action) {
+                            Ok(message) => BatchResult { target, success: true, message },
+                            Err(e) => BatchResult { target, success: false, message: Some(e.to_string()) }
+                        }
+                    };
+                    on_result(&result);
+                    results.lock().expect("batch results poisoned")[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results.into_inner()
+        .expect("batch results poisoned")
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn execute(api: &mut helm::Api, target: &BatchTarget, action: BatchAction) -> helm::api::Result<Option<String>> {
+    let release = target.release.clone();
+    let namespace = target.namespace.clone();
+    match action {
+        BatchAction::Uninstall => api.uninstall_chart(release, namespace).map(|_| None),
+        BatchAction::UpgradeLatest => api.upgrade_to_latest(release, namespace).map(|_| None),
+        BatchAction::Rollback => api.rollback(release, namespace, None).map(|_| None),
+        BatchAction::RefreshValues => api.get_values(release, namespace).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::executor;
+    use crate::helm::batch::{BatchAction, BatchTarget, run};
+
+    #[test]
+    fn should_run_action_for_every_target_and_keep_order() {
+        let targets = (0..10)
+            .map(|i| BatchTarget { release: format!("release-{}", i), namespace: "test".to_owned() })
+            .collect::<Vec<BatchTarget>>();
+        let streamed = Mutex::new(vec![]);
+
+        let results = run(
+            targets.clone(),
+            BatchAction::Uninstall,
+            3,
+            &AtomicBool::new(false),
+            |api| api,
+            || Box::new(FailingExecutor::failing_for("release-4")),
+            |r| streamed.lock().unwrap().push(r.target.release.clone()),
+        );
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(streamed.lock().unwrap().len(), 10);
+        for (result, target) in results.iter().zip(targets.iter()) {
+            assert_eq!(&result.target, target);
+            assert_eq!(result.success, target.release != "release-4");
+        }
+        assert_eq!(results[4].message, Some("release not found".to_owned()));
+    }
+
+    #[test]
+    fn should_return_values_on_refresh() {
+        let results = run(
+            vec![BatchTarget { release: "web".to_owned(), namespace: "test".to_owned() }],
+            BatchAction::RefreshValues,
+            2,
+            &AtomicBool::new(false),
+            |api| api,
+            || Box::new(FailingExecutor::failing_for("none")),
+            |_| {},
+        );
+
+        assert_eq!(results[0].message, Some("helm get values web -n test -o yaml".to_owned()));
+    }
+
+    #[test]
+    fn should_skip_remaining_targets_when_cancelled() {
+        let targets = (0..5)
+            .map(|i| BatchTarget { release: format!("release-{}", i), namespace: "test".to_owned() })
+            .collect::<Vec<BatchTarget>>();
+        let cancelled = AtomicBool::new(false);
+
+        let results = run(
+            targets,
+            BatchAction::Uninstall,
+            1,
+            &cancelled,
+            |api| api,
+            || Box::new(FailingExecutor::failing_for("none")),
+            |r| if r.target.release == "release-1" { cancelled.store(true, Ordering::Relaxed) },
+        );
+
+        assert_eq!(results.len(), 5);
+        assert!(results[0].success);
+        assert!(results[1].success);
+        for result in &results[2..] {
+            assert!(!result.success);
+            assert_eq!(result.message, Some("Cancelled".to_owned()));
+        }
+    }
+
+    struct FailingExecutor {
+        failing_release: String,
+        cmds: Vec<String>,
+    }
+
+    impl FailingExecutor {
+        fn failing_for(release: &str) -> Self {
+            FailingExecutor {
+                failing_release: release.to_owned(),
+                cmds: vec![],
+            }
+        }
+    }
+
+    impl executor::Executor for FailingExecutor {
+        fn command(&mut self, cmd: String) -> &mut dyn executor::Executor {
+            self.cmds = vec![cmd];
+            self
+        }
+
+        fn arg(&mut self, name: String) -> &mut dyn executor::Executor {
+            self.cmds.push(name);
+            self
+        }
+
+        fn env(&mut self, _name: String, _value: String) -> &mut dyn executor::Executor {
+            self
+        }
+
+        fn current_dir(&mut self, _dir: String) -> &mut dyn executor::Executor {
+            self
+        }
+
+        fn execute(&mut self) -> executor::Result<String> {
+            let executed = self.cmds.join(" ");
+            self.cmds.truncate(1);
+            if executed.contains(self.failing_release.as_str()) {
+                return Err(executor::Error::CannotExecuteCommand("release not found".to_owned()));
+            }
+            Ok(executed)
+        }
+    }
+}
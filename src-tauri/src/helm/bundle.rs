@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+const BUNDLE_API_VERSION: &'static str = "helmik/v1";
+const BUNDLE_KIND: &'static str = "ReleaseBundle";
+
+#[derive(Debug)]
+pub enum Error {
+    UnsupportedBundle(String),
+    InvalidBundle(String),
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::InvalidBundle(value.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BundleRepository {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BundleChart {
+    pub name: String,
+    pub version: String,
+    pub repository: BundleRepository,
+}
+
+/// Everything needed to install the same release on another cluster.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ReleaseBundle {
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    pub kind: String,
+    pub release: String,
+    pub namespace: String,
+    pub chart: BundleChart,
+    #[serde(default)]
+    pub values: serde_yaml::Value,
+}
+
+impl ReleaseBundle {
+    pub fn new(release: String, namespace: String, chart: BundleChart, values: serde_yaml::Value) -> Self {
+        ReleaseBundle {
+            api_version: BUNDLE_API_VERSION.to_owned(),
+            kind: BUNDLE_KIND.to_owned(),
+            release,
+            namespace,
+            chart,
+            values,
+        }
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let bundle: ReleaseBundle = serde_yaml::from_str(content)?;
+        if bundle.api_version != BUNDLE_API_VERSION || bundle.kind != BUNDLE_KIND {
+            return Err(Error::UnsupportedBundle(format!("{}/{}", bundle.api_version, bundle.kind)));
+        }
+        Ok(bundle)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// User values as a YAML document ready to be passed with `-f`, `None` when there are none.
+    pub fn values_yaml(&self) -> Result<Option<String>> {
+        match &self.values {
+            serde_yaml::Value::Null => Ok(None),
+            serde_yaml::Value::Mapping(m) if m.is_empty() => Ok(None),
+            v => Ok(Some(serde_yaml::to_string(v)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::bundle::{BundleChart, BundleRepository, ReleaseBundle};
+
+    #[test]
+    fn should_read_bundle_written_by_export() {
+        let values = serde_yaml::from_str("replicaCount: 2").unwrap();
+        let bundle = ReleaseBundle::new(
+            "web".to_owned(),
+            "default".to_owned(),
+            BundleChart {
+                name: "nginx".to_owned(),
+                version: "15.0.0".to_owned(),
+                repository: BundleRepository {
+                    name: "bitnami".to_owned(),
+                    url: "https://charts.bitnami.com/bitnami".to_owned(),
+                },
+            },
+            values,
+        );
+
+        let yaml = bundle.to_yaml().unwrap();
+        let imported = ReleaseBundle::from_yaml(yaml.as_str()).unwrap();
+
+        assert_eq!(imported, bundle);
+        assert_eq!(imported.values_yaml().unwrap(), Some("replicaCount: 2\n".to_owned()));
+    }
+
+    #[test]
+    fn should_reject_unknown_bundle_kind() {
+        let result = ReleaseBundle::from_yaml("apiVersion: v1\nkind: ConfigMap\nrelease: a\nnamespace: b\nchart: {name: c, version: '1', repository: {name: r, url: u}}");
+        assert!(result.is_err());
+    }
+}
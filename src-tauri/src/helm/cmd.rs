@@ -1,12 +1,15 @@
 use std::{env, fs};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use comrak;
 use serde::{Deserialize, Serialize};
 
 use crate::executor;
+use crate::helm;
 
 #[derive(Debug)]
 pub enum Error {
@@ -17,7 +20,8 @@ impl From<executor::Error> for Error {
     fn from(value: executor::Error) -> Self {
         match value {
             executor::Error::NoCommand => Error::SomethingWentWrong("no command".to_owned()),
-            executor::Error::CannotExecuteCommand(v) => Error::SomethingWentWrong(v)
+            executor::Error::CannotExecuteCommand(v) => Error::SomethingWentWrong(v),
+            executor::Error::CommandFailed { stderr, .. } => Error::SomethingWentWrong(stderr),
         }
     }
 }
@@ -81,22 +85,105 @@ mod helm_date_format {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Release as printed by `helm list -o json`.
+#[derive(Deserialize, Debug)]
+struct HelmRelease {
+    name: String,
+    namespace: String,
+    revision: String,
+    #[serde(with = "helm_date_format")]
+    updated: chrono::DateTime<chrono::FixedOffset>,
+    status: ReleaseStatus,
+    chart: String,
+    app_version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "HelmRelease")]
 pub struct Release {
     pub name: String,
     pub namespace: String,
-    pub revision: String,
+    pub revision: u32,
 
-    #[serde(with = "helm_date_format")]
+    #[serde(serialize_with = "helm_date_format::serialize")]
     pub updated: chrono::DateTime<chrono::FixedOffset>,
-    pub status: String,
+    pub status: ReleaseStatus,
     pub chart: String,
+    /// Chart name and version parsed from `chart`, empty when it cannot be parsed.
+    #[serde(rename = "chartReference")]
+    pub chart_reference: Option<ChartReference>,
 
-    #[serde(rename(serialize = "appVersion", deserialize = "app_version"))]
+    #[serde(rename = "appVersion")]
     pub app_version: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl TryFrom<HelmRelease> for Release {
+    type Error = String;
+
+    fn try_from(value: HelmRelease) -> std::result::Result<Self, Self::Error> {
+        let revision = value.revision.parse::<u32>()
+            .map_err(|e| format!("invalid revision {}: {}", value.revision, e))?;
+        Ok(Release {
+            name: value.name,
+            namespace: value.namespace,
+            revision,
+            updated: value.updated,
+            status: value.status,
+            chart_reference: ChartReference::parse(value.chart.as_str()),
+            chart: value.chart,
+            app_version: value.app_version,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseStatus {
+    Deployed,
+    Uninstalled,
+    Superseded,
+    Failed,
+    Uninstalling,
+    PendingInstall,
+    PendingUpgrade,
+    PendingRollback,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChartReference {
+    pub name: String,
+    pub version: String,
+}
+
+impl ChartReference {
+    /// Splits chart column from `helm list` (e.g. `nginx-ingress-4.7.1`) into chart name and version.
+    ///
+    /// Chart names may contain dashes and versions may contain prerelease parts
+    /// (`my-app-1.0.0-rc.1`), so the first dash after which a valid semver follows wins.
+    /// For charts not using semver the first dash followed by a digit is used.
+    pub fn parse(chart: &str) -> Option<Self> {
+        let dashes = chart.char_indices()
+            .filter(|(_, c)| *c == '-')
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        let is_semver = |v: &str| semver::Version::parse(v.trim_start_matches('v')).is_ok();
+        let starts_with_digit = |v: &str| v.trim_start_matches('v').starts_with(|c: char| c.is_ascii_digit());
+
+        dashes.iter()
+            .find(|i| is_semver(&chart[*i + 1..]))
+            .or(dashes.iter().find(|i| starts_with_digit(&chart[*i + 1..])))
+            .filter(|i| **i > 0)
+            .map(|i| ChartReference {
+                name: chart[..*i].to_owned(),
+                version: chart[*i + 1..].to_owned(),
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChartListItem {
     pub name: String,
     pub version: String,
@@ -106,14 +193,22 @@ pub struct ChartListItem {
     pub description: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChartDetails {
     pub info: ChartInfo,
     pub readme: String,
     pub values: String,
+    #[serde(rename = "valuesSchema", default)]
+    pub values_schema: Option<String>,
+    #[serde(default)]
+    pub templates: Vec<String>,
+    #[serde(default)]
+    pub crds: Vec<String>,
+    #[serde(default)]
+    pub subcharts: Vec<ChartInfo>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChartDependency {
     pub name: String,
     pub repository: String,
@@ -124,14 +219,14 @@ pub struct ChartDependency {
     pub alias: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChartMaintainer {
     pub name: String,
     pub url: Option<String>,
     pub email: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChartInfo {
     pub icon: Option<String>,
     pub name: String,
@@ -165,25 +260,71 @@ pub struct Repo {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InstallChartRequest {
     pub name: String,
+    /// Chart from repository as `repo/chart`, or path to local chart directory or `.tgz` archive.
     #[serde(rename = "chartName")]
     pub chart_name: String,
     pub namespace: String,
+    pub version: Option<String>,
     pub values: Option<String>,
+    /// Verifies chart provenance before installing, see `helm install --verify`.
+    #[serde(default)]
+    pub verify: bool,
 }
 
+pub const DEFAULT_BINARY: &'static str = "helm";
+
 pub struct Cmd<'a> {
     executor: &'a mut dyn executor::Executor,
+    kube_context: Option<String>,
+    keyring: Option<String>,
+    timeout: Option<Duration>,
 }
 
 impl<'a> Cmd<'a> {
     pub fn new(executor: &'a mut dyn executor::Executor) -> Self {
         Cmd {
-            executor: executor.command("helm".to_owned())
+            executor: executor.command(DEFAULT_BINARY.to_owned()),
+            kube_context: None,
+            keyring: None,
+            timeout: None,
+        }
+    }
+
+    /// Uses helm binary at given path instead of the one from PATH.
+    pub fn with_binary(self, binary: String) -> Self {
+        self.executor.command(binary);
+        self
+    }
+
+    /// Runs all commands against given kube context instead of the current one.
+    pub fn with_kube_context(mut self, kube_context: Option<String>) -> Self {
+        self.kube_context = kube_context;
+        self
+    }
+
+    /// Verifies charts with public keys from given keyring instead of helm default `~/.gnupg/pubring.gpg`.
+    pub fn with_keyring(mut self, keyring: Option<String>) -> Self {
+        self.keyring = keyring;
+        self
+    }
+
+    /// Passed as helm `--timeout` to commands changing releases, helm stops them cleanly instead of being killed.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn helm(&mut self) -> &mut dyn executor::Executor {
+        match self.kube_context.clone() {
+            Some(kube_context) => self.executor
+                .arg("--kube-context".to_owned())
+                .arg(kube_context),
+            None => self.executor
         }
     }
 
     pub fn delete_repository(&mut self, name: String) -> Result<()> {
-        self.executor
+        self.helm()
             .arg("repo".to_owned())
             .arg("remove".to_owned())
             .arg(name)
@@ -192,7 +333,7 @@ impl<'a> Cmd<'a> {
     }
 
     pub fn repos(&mut self) -> Result<Vec<Repo>> {
-        let result = self.executor
+        let result = self.helm()
             .arg("repo".to_owned())
             .arg("ls".to_owned())
             .arg("-o".to_owned())
@@ -203,7 +344,7 @@ impl<'a> Cmd<'a> {
         Ok(result)
     }
     pub fn add_repo(&mut self, name: String, url: String) -> Result<()> {
-        self.executor
+        self.helm()
             .arg("repo".to_owned())
             .arg("add".to_owned())
             .arg(name)
@@ -220,14 +361,14 @@ impl<'a> Cmd<'a> {
         Ok(())
     }
     pub fn refresh_repos(&mut self) -> Result<()> {
-        self.executor
+        self.helm()
             .arg("repo".to_owned())
             .arg("update".to_owned())
             .execute()?;
         Ok(())
     }
     pub fn list(&mut self) -> Result<Vec<Release>> {
-        let result = self.executor
+        let result = self.helm()
             .arg("list".to_owned())
             .arg("-A".to_owned())
             .arg("-o".to_owned())
@@ -238,7 +379,7 @@ impl<'a> Cmd<'a> {
     }
 
     pub fn version(&mut self) -> Result<String> {
-        let version = self.executor
+        let version = self.helm()
             .arg("version".to_owned())
             .arg("--template".to_owned())
             .arg("{{.Version}}".to_owned())
@@ -247,90 +388,317 @@ impl<'a> Cmd<'a> {
         Ok(version)
     }
 
+    /// Variables reported by `helm env`, e.g. `HELM_CACHE_HOME`.
+    pub fn env(&mut self) -> Result<BTreeMap<String, String>> {
+        let result = self.helm()
+            .arg("env".to_owned())
+            .execute()?;
+
+        Ok(parse_env(result.as_str()))
+    }
+
     pub fn find_charts_in_repo(&mut self, filter: String) -> Result<Vec<ChartListItem>> {
-        let result = self.executor
+        let result = self.helm()
+            .arg("-o".to_owned())
+            .arg("json".to_owned())
+            .arg("search".to_owned())
+            .arg("repo".to_owned())
+            .arg(filter)
+            .execute()?;
+        let result = serde_json::from_str(result.as_str())?;
+        Ok(result)
+    }
+
+    /// All versions of charts matching filter, not only the newest ones.
+    pub fn find_chart_versions(&mut self, filter: String) -> Result<Vec<ChartListItem>> {
+        let result = self.helm()
             .arg("-o".to_owned())
             .arg("json".to_owned())
             .arg("search".to_owned())
             .arg("repo".to_owned())
             .arg(filter)
+            .arg("--versions".to_owned())
+            .execute()?;
+        let result = serde_json::from_str(result.as_str())?;
+        Ok(result)
+    }
+
+    pub fn find_all_chart_versions_in_repo(&mut self) -> Result<Vec<ChartListItem>> {
+        let result = self.helm()
+            .arg("-o".to_owned())
+            .arg("json".to_owned())
+            .arg("search".to_owned())
+            .arg("repo".to_owned())
+            .arg("--versions".to_owned())
             .execute()?;
         let result = serde_json::from_str(result.as_str())?;
         Ok(result)
     }
 
     pub fn get_chart_details(&mut self, name: String) -> Result<ChartDetails> {
-        let info = self.executor
+        let info = self.helm()
             .arg("show".to_owned())
             .arg("chart".to_owned())
             .arg(name.clone())
             .execute()?;
         let info: ChartInfo = serde_yaml::from_str(info.as_str())?;
 
-        let readme = self.executor
+        let readme = self.helm()
             .arg("show".to_owned())
             .arg("readme".to_owned())
             .arg(name.clone())
             .execute()?;
 
-        let values = self.executor
+        let values = self.helm()
             .arg("show".to_owned())
             .arg("values".to_owned())
             .arg(name.clone())
             .execute()?;
 
-        let mut comrak_options = comrak::ComrakOptions::default();
-        comrak_options.extension.autolink = false;
-        comrak_options.extension.table = true;
-        let readme = comrak::markdown_to_html(readme.as_str(), &comrak_options);
-
         Ok(ChartDetails {
             info,
-            readme,
+            readme: render_readme(readme.as_str()),
             values,
+            values_schema: None,
+            templates: vec![],
+            crds: vec![],
+            subcharts: vec![],
         })
     }
     pub fn install_chart(&mut self, request: InstallChartRequest) -> Result<()> {
-        let command = self.executor
+        let values_file = values_file(&request)?;
+        let keyring = self.keyring.clone();
+        let timeout = self.timeout;
+        let command = self.helm()
             .arg("install".to_owned())
             .arg(request.name.clone())
             .arg(request.chart_name.clone())
             .arg("--namespace".to_owned())
             .arg(request.namespace.clone())
             .arg("--create-namespace".to_owned());
+        chart_args(command, &request, values_file.as_deref());
+        verify_args(command, request.verify, keyring);
+        timeout_args(command, timeout);
 
-        if request.values.is_some() {
-            let prepared_chart_name_prefix = request.chart_name.replace("/", "_").replace("\\", "_");
-            let values_file_name = prepared_chart_name_prefix + "_" + request.name.as_str() + ".yaml";
-            let values_file_name = env::temp_dir().join(values_file_name);
-            let mut values_file = File::create(values_file_name.clone())?;
-            values_file.write_all(request.values.unwrap().as_bytes())?;
-            values_file.flush()?;
+        let result = command.execute();
+        if let Some(values_file) = values_file {
+            fs::remove_file(values_file)?;
+        }
+        result?;
+        Ok(())
+    }
 
-            command
-                .arg("-f".to_owned())
-                .arg(values_file_name.to_str().expect("Cannot happen").to_string());
+    /// Installs release, or upgrades it when it already exists.
+    pub fn upgrade_install_chart(&mut self, request: InstallChartRequest) -> Result<()> {
+        let values_file = values_file(&request)?;
+        let keyring = self.keyring.clone();
+        let timeout = self.timeout;
+        let command = self.helm()
+            .arg("upgrade".to_owned())
+            .arg("--install".to_owned())
+            .arg(request.name.clone())
+            .arg(request.chart_name.clone())
+            .arg("--namespace".to_owned())
+            .arg(request.namespace.clone())
+            .arg("--create-namespace".to_owned());
+        chart_args(command, &request, values_file.as_deref());
+        verify_args(command, request.verify, keyring);
+        timeout_args(command, timeout);
 
+        let result = command.execute();
+        if let Some(values_file) = values_file {
+            fs::remove_file(values_file)?;
+        }
+        result?;
+        Ok(())
+    }
 
-            command.execute()?;
-            fs::remove_file(values_file_name)?;
-            return Ok(());
+    /// Renders manifests of the chart locally, like install would apply them.
+    pub fn template_chart(&mut self, request: InstallChartRequest) -> Result<String> {
+        let values_file = values_file(&request)?;
+        let keyring = self.keyring.clone();
+        let command = self.helm()
+            .arg("template".to_owned())
+            .arg(request.name.clone())
+            .arg(request.chart_name.clone())
+            .arg("--namespace".to_owned())
+            .arg(request.namespace.clone());
+        chart_args(command, &request, values_file.as_deref());
+        verify_args(command, request.verify, keyring);
+
+        let result = command.execute();
+        if let Some(values_file) = values_file {
+            fs::remove_file(values_file)?;
         }
-        command.execute()?;
+        Ok(result?)
+    }
+
+    /// Downloads dependencies of local chart into its `charts/` directory as locked in `Chart.lock`.
+    pub fn dependency_build(&mut self, chart_path: String) -> Result<()> {
+        self.helm()
+            .arg("dependency".to_owned())
+            .arg("build".to_owned())
+            .arg(chart_path)
+            .execute()?;
         Ok(())
     }
+
+    /// Updates `Chart.lock` to newest versions matching `Chart.yaml` and downloads them into `charts/`.
+    pub fn dependency_update(&mut self, chart_path: String) -> Result<()> {
+        self.helm()
+            .arg("dependency".to_owned())
+            .arg("update".to_owned())
+            .arg(chart_path)
+            .execute()?;
+        Ok(())
+    }
+
+    pub fn dependency_list(&mut self, chart_path: String) -> Result<Vec<helm::dependency::DependencyState>> {
+        let result = self.helm()
+            .arg("dependency".to_owned())
+            .arg("list".to_owned())
+            .arg(chart_path)
+            .execute()?;
+        Ok(helm::dependency::parse_list(result.as_str()))
+    }
+
+    /// Packages local chart, returns path of created archive.
+    pub fn package_chart(&mut self, request: helm::package::PackageRequest) -> Result<String> {
+        let command = self.helm()
+            .arg("package".to_owned())
+            .arg(request.chart_path);
+        if let Some(destination) = request.destination {
+            command.arg("--destination".to_owned()).arg(destination);
+        }
+        if let Some(version) = request.version {
+            command.arg("--version".to_owned()).arg(version);
+        }
+        if let Some(app_version) = request.app_version {
+            command.arg("--app-version".to_owned()).arg(app_version);
+        }
+        if let Some(sign) = request.sign {
+            command
+                .arg("--sign".to_owned())
+                .arg("--key".to_owned())
+                .arg(sign.key)
+                .arg("--keyring".to_owned())
+                .arg(sign.keyring);
+            if let Some(passphrase_file) = sign.passphrase_file {
+                command.arg("--passphrase-file".to_owned()).arg(passphrase_file);
+            }
+        }
+
+        let output = command.execute()?;
+        helm::package::parse_package_output(output.as_str())
+            .ok_or(Error::SomethingWentWrong(format!("Cannot find package path in: {}", output.trim())))
+    }
+
+    /// Pushes chart archive to OCI registry, e.g. `oci://registry.example.com/charts`.
+    pub fn push_chart(&mut self, archive: String, remote: String) -> Result<String> {
+        let output = self.helm()
+            .arg("push".to_owned())
+            .arg(archive)
+            .arg(remote)
+            .execute()?;
+        Ok(output)
+    }
+
+    /// Lints local chart, findings are reported also when linting fails.
+    pub fn lint_chart(&mut self, request: helm::lint::LintRequest) -> Result<helm::lint::LintResult> {
+        let command = self.helm()
+            .arg("lint".to_owned())
+            .arg(request.chart_path);
+        for values_file in request.values_files {
+            command
+                .arg("-f".to_owned())
+                .arg(values_file);
+        }
+        if request.strict {
+            command.arg("--strict".to_owned());
+        }
+
+        match command.execute() {
+            Ok(output) => Ok(helm::lint::LintResult { passed: true, findings: helm::lint::parse(output.as_str()) }),
+            Err(executor::Error::CommandFailed { stdout, .. }) if !stdout.trim().is_empty() => {
+                Ok(helm::lint::LintResult { passed: false, findings: helm::lint::parse(stdout.as_str()) })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn uninstall_chart(&mut self, release_name: String, namespace: String) -> Result<()> {
-        self.executor
+        let timeout = self.timeout;
+        let command = self.helm()
             .arg("uninstall".to_owned())
             .arg(release_name)
             .arg("-n".to_owned())
+            .arg(namespace);
+        timeout_args(command, timeout);
+        command.execute()?;
+        Ok(())
+    }
+
+    pub fn upgrade_chart(&mut self, release_name: String, chart_name: String, namespace: String, version: Option<String>, verify: bool) -> Result<()> {
+        let keyring = self.keyring.clone();
+        let timeout = self.timeout;
+        let command = self.helm()
+            .arg("upgrade".to_owned())
+            .arg(release_name)
+            .arg(chart_name)
+            .arg("-n".to_owned())
             .arg(namespace)
-            .execute()?;
+            .arg("--reuse-values".to_owned());
+
+        if let Some(version) = version {
+            command
+                .arg("--version".to_owned())
+                .arg(version);
+        }
+        verify_args(command, verify, keyring);
+        timeout_args(command, timeout);
+        command.execute()?;
+        Ok(())
+    }
+
+    /// Verifies signature of chart archive against its `.prov` file, failed verification is returned as result.
+    pub fn verify_chart(&mut self, archive: String) -> Result<helm::verify::VerificationResult> {
+        let keyring = self.keyring.clone();
+        let command = self.helm()
+            .arg("verify".to_owned())
+            .arg(archive);
+        if let Some(keyring) = keyring {
+            command.arg("--keyring".to_owned()).arg(keyring);
+        }
+
+        match command.execute() {
+            Ok(output) => Ok(helm::verify::parse(output.as_str())),
+            Err(executor::Error::CommandFailed { stderr, .. }) if !stderr.trim().is_empty() => {
+                Ok(helm::verify::parse_failure(stderr.as_str()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Rolls back to given revision, previous one when revision is not set.
+    pub fn rollback(&mut self, release_name: String, namespace: String, revision: Option<u32>) -> Result<()> {
+        let timeout = self.timeout;
+        let command = self.helm()
+            .arg("rollback".to_owned())
+            .arg(release_name);
+
+        if let Some(revision) = revision {
+            command.arg(revision.to_string());
+        }
+        command
+            .arg("-n".to_owned())
+            .arg(namespace);
+        timeout_args(command, timeout);
+        command.execute()?;
         Ok(())
     }
 
     pub fn get_values(&mut self, release_name: String, namespace: String) -> Result<String> {
-        let result = self.executor
+        let result = self.helm()
             .arg("get".to_owned())
             .arg("values".to_owned())
             .arg(release_name)
@@ -343,8 +711,78 @@ impl<'a> Cmd<'a> {
     }
 }
 
+/// Writes request values to a temporary file passed to helm with `-f`.
+fn values_file(request: &InstallChartRequest) -> Result<Option<PathBuf>> {
+    let Some(values) = request.values.as_ref() else {
+        return Ok(None);
+    };
+    let prepared_chart_name_prefix = request.chart_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect::<String>();
+    let values_file_name = prepared_chart_name_prefix + "_" + request.name.as_str() + ".yaml";
+    let values_file_name = env::temp_dir().join(values_file_name);
+    let mut values_file = File::create(values_file_name.clone())?;
+    values_file.write_all(values.as_bytes())?;
+    values_file.flush()?;
+    Ok(Some(values_file_name))
+}
+
+fn chart_args(command: &mut dyn executor::Executor, request: &InstallChartRequest, values_file: Option<&Path>) {
+    if let Some(version) = request.version.clone() {
+        command
+            .arg("--version".to_owned())
+            .arg(version);
+    }
+    if let Some(values_file) = values_file {
+        command
+            .arg("-f".to_owned())
+            .arg(values_file.to_str().expect("Cannot happen").to_string());
+    }
+}
+
+fn verify_args(command: &mut dyn executor::Executor, verify: bool, keyring: Option<String>) {
+    if !verify {
+        return;
+    }
+    command.arg("--verify".to_owned());
+    if let Some(keyring) = keyring {
+        command
+            .arg("--keyring".to_owned())
+            .arg(keyring);
+    }
+}
+
+fn timeout_args(command: &mut dyn executor::Executor, timeout: Option<Duration>) {
+    if let Some(timeout) = timeout {
+        command
+            .arg("--timeout".to_owned())
+            .arg(format!("{}s", timeout.as_secs()));
+    }
+}
+
+/// Chart README as HTML.
+pub fn render_readme(markdown: &str) -> String {
+    let mut comrak_options = comrak::ComrakOptions::default();
+    comrak_options.extension.autolink = false;
+    comrak_options.extension.table = true;
+    comrak::markdown_to_html(markdown, &comrak_options)
+}
+
+/// Parses `NAME="value"` lines printed by `helm env`.
+fn parse_env(output: &str) -> BTreeMap<String, String> {
+    output.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().trim_matches('"').to_owned()))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
+    use crate::helm::cmd::{ChartReference, parse_env, Release, ReleaseStatus};
     use crate::helm::Cmd;
 
     #[test]
@@ -354,6 +792,69 @@ mod test {
         assert_eq!(executor.get_execute(), "helm version --template {{.Version}}")
     }
 
+    #[test]
+    fn should_run_against_given_kube_context() {
+        let mut executor = Box::new(MockExecutor::default());
+        Cmd::new(executor.as_mut())
+            .with_kube_context(Some("prod-eu".to_owned()))
+            .version()
+            .expect("Cannot get version");
+        assert_eq!(executor.get_execute(), "helm --kube-context prod-eu version --template {{.Version}}")
+    }
+
+    #[test]
+    fn should_pass_timeout_to_release_changes() {
+        let mut executor = Box::new(MockExecutor::default());
+        Cmd::new(executor.as_mut())
+            .with_timeout(Some(Duration::from_secs(600)))
+            .uninstall_chart("web".to_owned(), "default".to_owned())
+            .expect("Cannot uninstall");
+        assert_eq!(executor.get_execute(), "helm uninstall web -n default --timeout 600s")
+    }
+
+    #[test]
+    fn should_parse_helm_env() {
+        let env = parse_env("HELM_BIN=\"helm\"\nHELM_DRIVER=\"secret\"\nHELM_KUBECONTEXT=\"\"\n\n");
+
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["HELM_DRIVER"], "secret");
+        assert_eq!(env["HELM_KUBECONTEXT"], "");
+    }
+
+    #[test]
+    fn should_parse_chart_reference() {
+        let parse = |chart: &str| ChartReference::parse(chart).map(|c| (c.name, c.version));
+
+        assert_eq!(parse("nginx-ingress-4.7.1"), Some(("nginx-ingress".to_owned(), "4.7.1".to_owned())));
+        assert_eq!(parse("my-app-1.0.0-rc.1"), Some(("my-app".to_owned(), "1.0.0-rc.1".to_owned())));
+        assert_eq!(parse("app-1.0.0-1"), Some(("app".to_owned(), "1.0.0-1".to_owned())));
+        assert_eq!(parse("k8s-2-agent-0.1.0"), Some(("k8s-2-agent".to_owned(), "0.1.0".to_owned())));
+        assert_eq!(parse("legacy-chart-1.2"), Some(("legacy-chart".to_owned(), "1.2".to_owned())));
+        assert_eq!(parse("nginx"), None);
+    }
+
+    #[test]
+    fn should_parse_release_from_helm_list() {
+        let releases: Vec<Release> = serde_json::from_str(r#"[{
+            "name": "web",
+            "namespace": "default",
+            "revision": "12",
+            "updated": "2023-05-01 10:00:00.000000000 +0000 UTC",
+            "status": "pending-upgrade",
+            "chart": "nginx-ingress-4.7.1",
+            "app_version": "1.8.1"
+        }]"#).unwrap();
+
+        assert_eq!(releases[0].revision, 12);
+        assert_eq!(releases[0].status, ReleaseStatus::PendingUpgrade);
+        assert_eq!(releases[0].chart_reference, Some(ChartReference { name: "nginx-ingress".to_owned(), version: "4.7.1".to_owned() }));
+
+        let serialized = serde_json::to_value(&releases[0]).unwrap();
+        assert_eq!(serialized["status"], "pending-upgrade");
+        assert_eq!(serialized["revision"], 12);
+        assert_eq!(serialized["chartReference"]["name"], "nginx-ingress");
+    }
+
 
     struct MockExecutor {
         cmds: Vec<String>,
@@ -384,8 +885,16 @@ mod test {
             self
         }
 
+        fn env(&mut self, _name: String, _value: String) -> &mut dyn crate::executor::Executor {
+            self
+        }
+
+        fn current_dir(&mut self, _dir: String) -> &mut dyn crate::executor::Executor {
+            self
+        }
+
         fn execute(&mut self) -> crate::executor::Result<String> {
             Ok("".to_owned())
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+use crate::helm::cmd::{ChartDependency, ChartInfo};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyStatus {
+    #[serde(rename = "ok")]
+    Ok,
+    #[serde(rename = "missing")]
+    Missing,
+    #[serde(rename = "wrongVersion")]
+    WrongVersion,
+    /// Subchart is a directory instead of archive.
+    #[serde(rename = "unpacked")]
+    Unpacked,
+    /// Archive holds chart with other name.
+    #[serde(rename = "misnamed")]
+    Misnamed,
+    #[serde(rename = "invalid")]
+    Invalid,
+}
+
+impl DependencyStatus {
+    fn parse(status: &str) -> Self {
+        match status.to_lowercase().as_str() {
+            "ok" => DependencyStatus::Ok,
+            "missing" => DependencyStatus::Missing,
+            "wrong version" => DependencyStatus::WrongVersion,
+            "unpacked" => DependencyStatus::Unpacked,
+            "misnamed" => DependencyStatus::Misnamed,
+            _ => DependencyStatus::Invalid,
+        }
+    }
+}
+
+/// Row of `helm dependency list`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DependencyState {
+    pub name: String,
+    pub version: String,
+    pub repository: String,
+    pub status: DependencyStatus,
+    /// Status as printed by helm, e.g. `invalid version`.
+    #[serde(rename = "statusText")]
+    pub status_text: String,
+}
+
+/// Parses tab separated table printed by `helm dependency list`, charts without dependencies print only a warning.
+pub fn parse_list(output: &str) -> Vec<DependencyState> {
+    output.lines()
+        .skip_while(|line| !line.starts_with("NAME"))
+        .skip(1)
+        .filter_map(|line| {
+            let columns = line.split('\t').map(|c| c.trim()).collect::<Vec<&str>>();
+            match columns.as_slice() {
+                [name, version, repository, status, ..] if !name.is_empty() => Some(DependencyState {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    repository: repository.to_string(),
+                    status: DependencyStatus::parse(status),
+                    status_text: status.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolvedBy {
+    #[serde(rename = "condition")]
+    Condition,
+    #[serde(rename = "tags")]
+    Tags,
+    /// Neither condition nor tags are set in values, subchart is enabled.
+    #[serde(rename = "default")]
+    Default,
+}
+
+/// Whether subchart is enabled for given values and why.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SubchartState {
+    pub name: String,
+    pub alias: Option<String>,
+    pub condition: Option<String>,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+    #[serde(rename = "resolvedBy")]
+    pub resolved_by: ResolvedBy,
+}
+
+/// Resolves subchart conditions and tags like helm does on install.
+///
+/// The first condition path set to a boolean wins, otherwise subchart is enabled when any of its tags is true
+/// and disabled when all set tags are false.
+pub fn resolve(info: &ChartInfo, values: &serde_yaml::Value) -> Vec<SubchartState> {
+    info.dependencies.iter()
+        .flatten()
+        .map(|dependency| {
+            let (enabled, resolved_by) = condition(dependency, values)
+                .map(|enabled| (enabled, ResolvedBy::Condition))
+                .or(tags(dependency, values).map(|enabled| (enabled, ResolvedBy::Tags)))
+                .unwrap_or((true, ResolvedBy::Default));
+            SubchartState {
+                name: dependency.name.clone(),
+                alias: dependency.alias.clone(),
+                condition: dependency.condition.clone(),
+                tags: dependency.tags.clone().unwrap_or_default(),
+                enabled,
+                resolved_by,
+            }
+        })
+        .collect()
+}
+
+fn condition(dependency: &ChartDependency, values: &serde_yaml::Value) -> Option<bool> {
+    dependency.condition.as_deref()?
+        .split(',')
+        .map(|path| path.trim())
+        .find_map(|path| path.split('.')
+            .try_fold(values, |value, key| value.get(key))
+            .and_then(|value| value.as_bool()))
+}
+
+fn tags(dependency: &ChartDependency, values: &serde_yaml::Value) -> Option<bool> {
+    let set = dependency.tags.iter()
+        .flatten()
+        .filter_map(|tag| values.get("tags")?.get(tag.as_str())?.as_bool())
+        .collect::<Vec<bool>>();
+    match set.is_empty() {
+        true => None,
+        false => Some(set.contains(&true)),
+    }
+}
+
+/// Deep merge the same way helm merges chart defaults and `-f` files, later values win and maps are merged recursively.
+///
+/// Empty values file changes nothing, while `key: null` is kept so helm removes that default.
+pub fn merge_values(target: &mut serde_yaml::Value, source: serde_yaml::Value) {
+    match (target, source) {
+        (_, serde_yaml::Value::Null) => {}
+        (serde_yaml::Value::Mapping(target), serde_yaml::Value::Mapping(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) if !value.is_null() => merge_values(existing, value),
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::cmd::ChartInfo;
+    use crate::helm::dependency::{DependencyStatus, merge_values, parse_list, resolve, ResolvedBy};
+
+    #[test]
+    fn should_parse_dependency_list() {
+        let output = "NAME      \tVERSION\tREPOSITORY                        \tSTATUS         \n\
+                      redis     \t17.x.x \thttps://charts.bitnami.com/bitnami\tok             \n\
+                      common    \t2.x.x  \toci://registry-1.docker.io/bitnamicharts\twrong version  \n\
+                      postgresql\t12.5.8 \thttps://charts.bitnami.com/bitnami\tmissing        \n\n";
+
+        let dependencies = parse_list(output);
+
+        assert_eq!(dependencies.len(), 3);
+        assert_eq!(dependencies[0].name, "redis");
+        assert_eq!(dependencies[1].status, DependencyStatus::WrongVersion);
+        assert_eq!(dependencies[2].status, DependencyStatus::Missing);
+        assert!(parse_list("WARNING: no dependencies at /charts/app/charts\n").is_empty());
+    }
+
+    #[test]
+    fn should_resolve_conditions_before_tags() {
+        let info: ChartInfo = serde_yaml::from_str("name: app\nversion: 1.0.0\ndependencies:\n\
+            - name: redis\n  version: 17.x.x\n  repository: https://charts.bitnami.com/bitnami\n  condition: cache.enabled,redis.enabled\n  tags: [backend]\n\
+            - name: postgresql\n  version: 12.x.x\n  repository: https://charts.bitnami.com/bitnami\n  tags: [backend, database]\n\
+            - name: common\n  version: 2.x.x\n  repository: https://charts.bitnami.com/bitnami\n  alias: shared\n").unwrap();
+        let mut values = serde_yaml::from_str("redis:\n  enabled: true\ntags:\n  backend: false\n").unwrap();
+        merge_values(&mut values, serde_yaml::from_str("redis:\n  enabled: false\ntags:\n  database: true\n").unwrap());
+
+        let subcharts = resolve(&info, &values);
+
+        assert_eq!((subcharts[0].enabled, subcharts[0].resolved_by), (false, ResolvedBy::Condition));
+        assert_eq!((subcharts[1].enabled, subcharts[1].resolved_by), (true, ResolvedBy::Tags));
+        assert_eq!((subcharts[2].enabled, subcharts[2].resolved_by), (true, ResolvedBy::Default));
+        assert_eq!(subcharts[2].alias, Some("shared".to_owned()));
+    }
+
+    #[test]
+    fn should_merge_values_keeping_explicit_nulls() {
+        let mut values: serde_yaml::Value = serde_yaml::from_str("service:\n  type: LoadBalancer\n  port: 80\nreplicaCount: 1\n").unwrap();
+
+        merge_values(&mut values, serde_yaml::Value::Null);
+        merge_values(&mut values, serde_yaml::from_str("service:\n  port: 8080\n  type: null\nextra: true\n").unwrap());
+
+        assert_eq!(values, serde_yaml::from_str::<serde_yaml::Value>("service:\n  type: null\n  port: 8080\nreplicaCount: 1\nextra: true\n").unwrap());
+    }
+}
@@ -0,0 +1,149 @@
+use std::env;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::executor::Executor;
+use crate::helm;
+
+/// Oldest helm release providing every command and flag used by [helm::Cmd].
+pub const MINIMUM_VERSION: &'static str = "3.8.0";
+
+#[cfg(windows)]
+const BINARY_NAME: &'static str = "helm.exe";
+#[cfg(not(windows))]
+const BINARY_NAME: &'static str = "helm";
+
+#[cfg(windows)]
+const COMMON_DIRS: [&'static str; 2] = ["C:\\ProgramData\\chocolatey\\bin", "C:\\Program Files\\helm"];
+#[cfg(not(windows))]
+const COMMON_DIRS: [&'static str; 5] = ["/usr/local/bin", "/usr/bin", "/opt/homebrew/bin", "/home/linuxbrew/.linuxbrew/bin", "/snap/bin"];
+
+#[cfg(windows)]
+const HOME_DIRS: [&'static str; 1] = ["scoop\\shims"];
+#[cfg(not(windows))]
+const HOME_DIRS: [&'static str; 2] = [".local/bin", "bin"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HelmInstallation {
+    pub path: String,
+    pub version: Option<String>,
+    pub supported: bool,
+    pub error: Option<String>,
+}
+
+/// Result of checking configured helm, shown as setup error on first run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetupStatus {
+    pub ready: bool,
+    pub installation: HelmInstallation,
+    #[serde(rename = "minimumVersion")]
+    pub minimum_version: String,
+    pub message: Option<String>,
+}
+
+/// Helm binaries found in PATH and common installation directories, without duplicates.
+pub fn discover_candidates() -> Vec<PathBuf> {
+    let mut dirs = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<PathBuf>>())
+        .unwrap_or_default();
+    dirs.extend(COMMON_DIRS.iter().map(PathBuf::from));
+    if let Some(home) = home_dir() {
+        dirs.extend(HOME_DIRS.iter().map(|d| home.join(d)));
+    }
+
+    let mut candidates: Vec<PathBuf> = vec![];
+    for dir in dirs {
+        let candidate = dir.join(BINARY_NAME);
+        if !candidate.is_file() {
+            continue;
+        }
+        let resolved = candidate.canonicalize().unwrap_or(candidate.clone());
+        let already_found = candidates.iter()
+            .any(|c| c.canonicalize().unwrap_or(c.clone()) == resolved);
+        if !already_found {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// Runs `helm version` with given binary and checks it against [MINIMUM_VERSION].
+pub fn inspect(executor: &mut dyn Executor, binary: &str) -> HelmInstallation {
+    match helm::Api::new(executor).with_helm_binary(Some(binary.to_owned())).version() {
+        Ok(version) => {
+            let version = version.trim().to_owned();
+            HelmInstallation {
+                path: binary.to_owned(),
+                supported: is_supported(version.as_str()),
+                version: Some(version),
+                error: None,
+            }
+        }
+        Err(e) => HelmInstallation {
+            path: binary.to_owned(),
+            version: None,
+            supported: false,
+            error: Some(e.to_string()),
+        }
+    }
+}
+
+pub fn setup_status(installation: HelmInstallation) -> SetupStatus {
+    let message = match (&installation.version, installation.supported) {
+        (_, true) => None,
+        (None, _) => Some(format!(
+            "Helm was not found at {}. Install Helm {} or newer, or choose helm binary in settings.",
+            installation.path, MINIMUM_VERSION
+        )),
+        (Some(version), false) => Some(format!(
+            "Helm {} is not supported, Helmik requires Helm {} or newer.",
+            version, MINIMUM_VERSION
+        )),
+    };
+    SetupStatus {
+        ready: installation.supported,
+        installation,
+        minimum_version: MINIMUM_VERSION.to_owned(),
+        message,
+    }
+}
+
+/// Whether version printed by `helm version` (e.g. `v3.10.3`) is a supported Helm 3.
+pub fn is_supported(version: &str) -> bool {
+    let minimum = semver::Version::parse(MINIMUM_VERSION).expect("Cannot happen");
+    semver::Version::parse(version.trim().trim_start_matches('v'))
+        .map(|v| v.major == minimum.major && v >= minimum)
+        .unwrap_or(false)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or(env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::discovery::{inspect, is_supported, setup_status};
+
+    #[test]
+    fn should_accept_only_supported_helm_3_versions() {
+        assert!(is_supported("v3.10.3"));
+        assert!(is_supported("v3.8.0\n"));
+        assert!(!is_supported("v3.7.2"));
+        assert!(!is_supported("v2.17.0"));
+        assert!(!is_supported("v4.0.0"));
+        assert!(!is_supported("unknown"));
+    }
+
+    #[test]
+    fn should_report_missing_helm() {
+        let mut executor = crate::executor::DefaultExecutor::new();
+        let status = setup_status(inspect(&mut executor, "/nonexistent/helm"));
+
+        assert!(!status.ready);
+        assert_eq!(status.installation.version, None);
+        assert!(status.message.unwrap().contains("was not found"));
+    }
+}
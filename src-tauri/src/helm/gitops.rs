@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::helm::bundle::ReleaseBundle;
+use crate::helm::package::OCI_SCHEME;
+
+const ARGOCD_NAMESPACE: &'static str = "argocd";
+const FLUX_NAMESPACE: &'static str = "flux-system";
+const IN_CLUSTER_SERVER: &'static str = "https://kubernetes.default.svc";
+
+#[derive(Debug)]
+pub enum Error {
+    CannotGenerateManifest(String),
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::CannotGenerateManifest(value.to_string())
+    }
+}
+
+impl From<crate::helm::bundle::Error> for Error {
+    fn from(value: crate::helm::bundle::Error) -> Self {
+        match value {
+            crate::helm::bundle::Error::UnsupportedBundle(v) => Error::CannotGenerateManifest(v),
+            crate::helm::bundle::Error::InvalidBundle(v) => Error::CannotGenerateManifest(v),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::CannotGenerateManifest(value.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum GitOpsFormat {
+    #[serde(rename = "argocd")]
+    ArgoCd,
+    #[serde(rename = "flux")]
+    Flux,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub content: String,
+}
+
+pub fn manifests(bundle: &ReleaseBundle, format: GitOpsFormat) -> Result<Vec<Manifest>> {
+    match format {
+        GitOpsFormat::ArgoCd => Ok(vec![argocd_application(bundle)?]),
+        GitOpsFormat::Flux => Ok(vec![flux_helm_repository(bundle)?, flux_helm_release(bundle)?])
+    }
+}
+
+/// Joins manifests into one multi-document YAML.
+pub fn join(manifests: &[Manifest]) -> String {
+    manifests.iter()
+        .map(|m| m.content.as_str())
+        .collect::<Vec<&str>>()
+        .join("---\n")
+}
+
+fn argocd_application(bundle: &ReleaseBundle) -> Result<Manifest> {
+    let mut helm = json!({
+        "releaseName": bundle.release,
+    });
+    if let Some(values) = bundle.values_yaml()? {
+        helm["values"] = json!(values);
+    }
+
+    let application = json!({
+        "apiVersion": "argoproj.io/v1alpha1",
+        "kind": "Application",
+        "metadata": {
+            "name": bundle.release,
+            "namespace": ARGOCD_NAMESPACE,
+        },
+        "spec": {
+            "project": "default",
+            "source": {
+                "repoURL": bundle.chart.repository.url,
+                "chart": bundle.chart.name,
+                "targetRevision": bundle.chart.version,
+                "helm": helm,
+            },
+            "destination": {
+                "server": IN_CLUSTER_SERVER,
+                "namespace": bundle.namespace,
+            },
+            "syncPolicy": {
+                "syncOptions": ["CreateNamespace=true"],
+            },
+        },
+    });
+
+    Ok(Manifest {
+        file_name: format!("{}-application.yaml", bundle.release),
+        content: serde_yaml::to_string(&application)?,
+    })
+}
+
+fn flux_helm_repository(bundle: &ReleaseBundle) -> Result<Manifest> {
+    let mut repository = json!({
+        "apiVersion": "source.toolkit.fluxcd.io/v1beta2",
+        "kind": "HelmRepository",
+        "metadata": {
+            "name": bundle.chart.repository.name,
+            "namespace": FLUX_NAMESPACE,
+        },
+        "spec": {
+            "interval": "1h",
+            "url": bundle.chart.repository.url,
+        },
+    });
+    if bundle.chart.repository.url.starts_with(OCI_SCHEME) {
+        repository["spec"]["type"] = json!("oci");
+    }
+
+    Ok(Manifest {
+        file_name: format!("{}-helmrepository.yaml", bundle.chart.repository.name),
+        content: serde_yaml::to_string(&repository)?,
+    })
+}
+
+fn flux_helm_release(bundle: &ReleaseBundle) -> Result<Manifest> {
+    let mut release = json!({
+        "apiVersion": "helm.toolkit.fluxcd.io/v2beta1",
+        "kind": "HelmRelease",
+        "metadata": {
+            "name": bundle.release,
+            "namespace": FLUX_NAMESPACE,
+        },
+        "spec": {
+            "interval": "10m",
+            "releaseName": bundle.release,
+            "targetNamespace": bundle.namespace,
+            // keeps helm release record next to the release, so Flux adopts the existing one
+            "storageNamespace": bundle.namespace,
+            "install": {
+                "createNamespace": true,
+            },
+            "chart": {
+                "spec": {
+                    "chart": bundle.chart.name,
+                    "version": bundle.chart.version,
+                    "sourceRef": {
+                        "kind": "HelmRepository",
+                        "name": bundle.chart.repository.name,
+                        "namespace": FLUX_NAMESPACE,
+                    },
+                },
+            },
+        },
+    });
+    if !bundle.values.is_null() {
+        release["spec"]["values"] = serde_json::to_value(&bundle.values)?;
+    }
+
+    Ok(Manifest {
+        file_name: format!("{}-helmrelease.yaml", bundle.release),
+        content: serde_yaml::to_string(&release)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::bundle::{BundleChart, BundleRepository, ReleaseBundle};
+    use crate::helm::gitops::{GitOpsFormat, join, manifests};
+
+    fn bundle() -> ReleaseBundle {
+        ReleaseBundle::new(
+            "web".to_owned(),
+            "frontend".to_owned(),
+            BundleChart {
+                name: "nginx".to_owned(),
+                version: "15.0.0".to_owned(),
+                repository: BundleRepository {
+                    name: "bitnami".to_owned(),
+                    url: "https://charts.bitnami.com/bitnami".to_owned(),
+                },
+            },
+            serde_yaml::from_str("replicaCount: 2").unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_generate_argocd_application() {
+        let result = manifests(&bundle(), GitOpsFormat::ArgoCd).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].file_name, "web-application.yaml");
+
+        let application: serde_yaml::Value = serde_yaml::from_str(result[0].content.as_str()).unwrap();
+        assert_eq!(application["kind"], "Application");
+        assert_eq!(application["spec"]["source"]["repoURL"], "https://charts.bitnami.com/bitnami");
+        assert_eq!(application["spec"]["source"]["chart"], "nginx");
+        assert_eq!(application["spec"]["source"]["targetRevision"], "15.0.0");
+        assert_eq!(application["spec"]["source"]["helm"]["values"], "replicaCount: 2\n");
+        assert_eq!(application["spec"]["destination"]["namespace"], "frontend");
+    }
+
+    #[test]
+    fn should_generate_flux_repository_and_release() {
+        let result = manifests(&bundle(), GitOpsFormat::Flux).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let repository: serde_yaml::Value = serde_yaml::from_str(result[0].content.as_str()).unwrap();
+        assert_eq!(repository["kind"], "HelmRepository");
+        assert_eq!(repository["metadata"]["name"], "bitnami");
+        assert!(repository["spec"].get("type").is_none());
+
+        let release: serde_yaml::Value = serde_yaml::from_str(result[1].content.as_str()).unwrap();
+        assert_eq!(release["kind"], "HelmRelease");
+        assert_eq!(release["spec"]["targetNamespace"], "frontend");
+        assert_eq!(release["spec"]["storageNamespace"], "frontend");
+        assert_eq!(release["spec"]["chart"]["spec"]["sourceRef"]["name"], "bitnami");
+        assert_eq!(release["spec"]["values"]["replicaCount"], 2);
+
+        assert_eq!(join(&result).matches("---\n").count(), 1);
+    }
+
+    #[test]
+    fn should_generate_flux_oci_repository() {
+        let mut bundle = bundle();
+        bundle.chart.repository.url = "oci://registry-1.docker.io/bitnamicharts".to_owned();
+
+        let result = manifests(&bundle, GitOpsFormat::Flux).unwrap();
+        let repository: serde_yaml::Value = serde_yaml::from_str(result[0].content.as_str()).unwrap();
+
+        assert_eq!(repository["spec"]["type"], "oci");
+        assert_eq!(repository["spec"]["url"], "oci://registry-1.docker.io/bitnamicharts");
+    }
+}
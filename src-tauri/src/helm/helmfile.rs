@@ -0,0 +1,277 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helm::cmd::{InstallChartRequest, Repo};
+use crate::helm::dependency::merge_values;
+
+const DEFAULT_NAMESPACE: &'static str = "default";
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidHelmfile(String),
+    CannotReadValues(String),
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::InvalidHelmfile(value.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Only keys which are applied are accepted, ignoring others would install something else than the helmfile describes.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Helmfile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<HelmfileRepository>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub releases: Vec<HelmfileRelease>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HelmfileRepository {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HelmfileRelease {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub chart: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Each entry is either a path to values file (relative to helmfile) or inline values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<serde_yaml::Value>,
+    /// Applied over values like helm `--set`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub set: Vec<HelmfileSetValue>,
+    /// Applied over values like helm `--set-string`.
+    #[serde(rename = "setString", default, skip_serializing_if = "Vec::is_empty")]
+    pub set_string: Vec<HelmfileSetValue>,
+    /// Verifies chart provenance on install.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verify: bool,
+}
+
+/// Single value set by dotted path, e.g. `service.type`, dots in keys are escaped as `\.`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HelmfileSetValue {
+    pub name: String,
+    pub value: serde_yaml::Value,
+}
+
+impl HelmfileSetValue {
+    /// Nested values with the value at the end of the path.
+    fn to_values(&self, value: serde_yaml::Value) -> Result<serde_yaml::Value> {
+        let mut keys = vec![String::new()];
+        let mut chars = self.name.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => keys.last_mut().expect("Cannot happen").extend(chars.next()),
+                '.' => keys.push(String::new()),
+                '[' => return Err(Error::InvalidHelmfile(format!("set {}: list indexes are not supported", self.name))),
+                c => keys.last_mut().expect("Cannot happen").push(c),
+            }
+        }
+        if keys.iter().any(|k| k.is_empty()) {
+            return Err(Error::InvalidHelmfile(format!("set {}: invalid name", self.name)));
+        }
+        Ok(keys.into_iter().rev().fold(value, |value, key| {
+            let mut mapping = serde_yaml::Mapping::new();
+            mapping.insert(serde_yaml::Value::String(key), value);
+            serde_yaml::Value::Mapping(mapping)
+        }))
+    }
+
+    fn string_value(&self) -> Result<serde_yaml::Value> {
+        match &self.value {
+            serde_yaml::Value::String(s) => Ok(serde_yaml::Value::String(s.clone())),
+            serde_yaml::Value::Number(n) => Ok(serde_yaml::Value::String(n.to_string())),
+            serde_yaml::Value::Bool(b) => Ok(serde_yaml::Value::String(b.to_string())),
+            _ => Err(Error::InvalidHelmfile(format!("setString {}: value is not a string", self.name))),
+        }
+    }
+}
+
+/// Repositories and releases from helmfile, ready to be applied.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HelmfilePlan {
+    pub repositories: Vec<Repo>,
+    pub releases: Vec<InstallChartRequest>,
+}
+
+impl Helmfile {
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let mut documents = serde_yaml::Deserializer::from_str(content);
+        let helmfile = match documents.next() {
+            Some(document) => Helmfile::deserialize(document)?,
+            None => Helmfile::default(),
+        };
+        if documents.next().is_some() {
+            return Err(Error::InvalidHelmfile("helmfiles with multiple documents are not supported".to_owned()));
+        }
+        Ok(helmfile)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Converts helmfile into install requests, values files are resolved against `base_dir`.
+    pub fn plan(&self, base_dir: &Path) -> Result<HelmfilePlan> {
+        let repositories = self.repositories.iter()
+            .map(|r| Repo { name: r.name.clone(), url: r.url.clone() })
+            .collect();
+
+        let mut releases = vec![];
+        for release in self.releases.iter() {
+            releases.push(InstallChartRequest {
+                name: release.name.clone(),
+                chart_name: release.chart_path(base_dir),
+                namespace: release.namespace.clone().unwrap_or(DEFAULT_NAMESPACE.to_owned()),
+                version: release.version.clone(),
+                values: release.merged_values(base_dir)?,
+                verify: release.verify,
+            });
+        }
+
+        Ok(HelmfilePlan {
+            repositories,
+            releases,
+        })
+    }
+}
+
+impl HelmfileRelease {
+    /// Local chart directory or archive resolved against helmfile directory, repository charts are kept as they are.
+    fn chart_path(&self, base_dir: &Path) -> String {
+        let local = base_dir.join(&self.chart);
+        match local.exists() {
+            true => local.to_string_lossy().to_string(),
+            false => self.chart.clone(),
+        }
+    }
+
+    fn merged_values(&self, base_dir: &Path) -> Result<Option<String>> {
+        let mut merged = serde_yaml::Value::Null;
+        for entry in self.values.iter() {
+            let values = match entry {
+                serde_yaml::Value::String(file) => {
+                    let content = fs::read_to_string(base_dir.join(file))
+                        .map_err(|e| Error::CannotReadValues(format!("{}: {}", file, e)))?;
+                    serde_yaml::from_str(content.as_str())?
+                }
+                inline => inline.clone()
+            };
+            merge_values(&mut merged, values);
+        }
+        for set in self.set.iter() {
+            merge_values(&mut merged, set.to_values(set.value.clone())?);
+        }
+        for set in self.set_string.iter() {
+            merge_values(&mut merged, set.to_values(set.string_value()?)?);
+        }
+
+        match merged {
+            serde_yaml::Value::Null => Ok(None),
+            v => Ok(Some(serde_yaml::to_string(&v)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+
+    use crate::helm::helmfile::{Error, Helmfile};
+
+    const HELMFILE: &'static str = r#"
+repositories:
+  - name: bitnami
+    url: https://charts.bitnami.com/bitnami
+releases:
+  - name: web
+    namespace: frontend
+    chart: bitnami/nginx
+    version: 15.0.0
+    values:
+      - replicaCount: 1
+        service:
+          type: ClusterIP
+      - service:
+          type: LoadBalancer
+  - name: cache
+    chart: bitnami/redis
+"#;
+
+    #[test]
+    fn should_convert_helmfile_into_install_requests() {
+        let plan = Helmfile::from_yaml(HELMFILE).unwrap().plan(env::temp_dir().as_path()).unwrap();
+
+        assert_eq!(plan.repositories.len(), 1);
+        assert_eq!(plan.repositories[0].name, "bitnami");
+        assert_eq!(plan.releases.len(), 2);
+
+        let web = &plan.releases[0];
+        assert_eq!(web.chart_name, "bitnami/nginx");
+        assert_eq!(web.namespace, "frontend");
+        assert_eq!(web.version, Some("15.0.0".to_owned()));
+        assert_eq!(web.values, Some("replicaCount: 1\nservice:\n  type: LoadBalancer\n".to_owned()));
+
+        let cache = &plan.releases[1];
+        assert_eq!(cache.namespace, "default");
+        assert_eq!(cache.version, None);
+        assert_eq!(cache.values, None);
+    }
+
+    #[test]
+    fn should_read_values_files_relative_to_helmfile() {
+        let base_dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(base_dir.join("values")).unwrap();
+        fs::create_dir_all(base_dir.join("charts/app")).unwrap();
+        fs::write(base_dir.join("values/web.yaml"), "replicaCount: 3\n").unwrap();
+
+        let helmfile = Helmfile::from_yaml("releases:\n  - name: web\n    chart: bitnami/nginx\n    values:\n      - values/web.yaml\n  - name: app\n    chart: ./charts/app\n").unwrap();
+        let plan = helmfile.plan(base_dir.as_path()).unwrap();
+        fs::remove_dir_all(&base_dir).unwrap();
+
+        assert_eq!(plan.releases[0].values, Some("replicaCount: 3\n".to_owned()));
+        assert_eq!(plan.releases[0].chart_name, "bitnami/nginx");
+        assert_eq!(plan.releases[1].chart_name, base_dir.join("./charts/app").to_string_lossy());
+    }
+
+    #[test]
+    fn should_apply_set_values_over_values() {
+        let helmfile = Helmfile::from_yaml("releases:\n  - name: web\n    chart: bitnami/nginx\n    values:\n      - service:\n          type: ClusterIP\n    set:\n      - name: service.type\n        value: LoadBalancer\n      - name: podAnnotations.prometheus\\.io/port\n        value: 9113\n    setString:\n      - name: image.tag\n        value: 1.25\n").unwrap();
+        let plan = helmfile.plan(env::temp_dir().as_path()).unwrap();
+
+        let values: serde_yaml::Value = serde_yaml::from_str(plan.releases[0].values.as_ref().unwrap()).unwrap();
+        assert_eq!(values["service"]["type"], "LoadBalancer");
+        assert_eq!(values["podAnnotations"]["prometheus.io/port"], 9113);
+        assert_eq!(values["image"]["tag"], "1.25");
+    }
+
+    #[test]
+    fn should_reject_helmfile_parts_which_cannot_be_applied() {
+        let secrets = Helmfile::from_yaml("releases:\n  - name: web\n    chart: bitnami/nginx\n    secrets:\n      - secrets.yaml\n");
+        let documents = Helmfile::from_yaml("releases:\n  - name: web\n    chart: bitnami/nginx\n---\nreleases:\n  - name: cache\n    chart: bitnami/redis\n");
+        let list_index = Helmfile::from_yaml("releases:\n  - name: web\n    chart: bitnami/nginx\n    set:\n      - name: hosts[0]\n        value: example.com\n")
+            .unwrap()
+            .plan(env::temp_dir().as_path());
+
+        assert!(matches!(secrets, Err(Error::InvalidHelmfile(e)) if e.contains("secrets")));
+        assert!(matches!(documents, Err(Error::InvalidHelmfile(e)) if e.contains("multiple documents")));
+        assert!(matches!(list_index, Err(Error::InvalidHelmfile(e)) if e.contains("hosts[0]")));
+    }
+}
@@ -0,0 +1,603 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::helm::cmd::ChartListItem;
+use crate::helm::outdated::parse_version;
+
+#[derive(Debug)]
+pub enum Error {
+    CannotFetch(String),
+    InvalidIndex(String),
+    ChartNotFound(String),
+    /// Downloaded archive differs from the one published in the index.
+    DigestMismatch(String),
+}
+
+impl From<ureq::Error> for Error {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            ureq::Error::Status(status, response) => Error::CannotFetch(format!("{} returned {}", response.get_url(), status)),
+            ureq::Error::Transport(e) => Error::CannotFetch(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::CannotFetch(value.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::InvalidIndex(value.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Chart repository as configured in helm `repositories.yaml`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Repository {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sends credentials also to chart URLs on other hosts than the repository.
+    #[serde(default)]
+    pub pass_credentials_all: bool,
+    #[serde(rename = "caFile", default)]
+    pub ca_file: Option<String>,
+    #[serde(rename = "certFile", default)]
+    pub cert_file: Option<String>,
+    #[serde(rename = "keyFile", default)]
+    pub key_file: Option<String>,
+    #[serde(default)]
+    pub insecure_skip_tls_verify: bool,
+}
+
+impl Repository {
+    fn authorization(&self) -> Option<String> {
+        let username = self.username.as_deref().filter(|u| !u.is_empty())?;
+        let password = self.password.as_deref().unwrap_or_default();
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        Some(format!("Basic {}", credentials))
+    }
+
+    /// Custom CA, client certificate and skipped verification are left to helm, direct requests would not honor them.
+    fn check_tls(&self) -> Result<()> {
+        let configured = |file: &Option<String>| file.as_deref().map_or(false, |f| !f.is_empty());
+        match configured(&self.ca_file) || configured(&self.cert_file) || configured(&self.key_file) || self.insecure_skip_tls_verify {
+            true => Err(Error::CannotFetch(format!("repository {} uses TLS options which can be used only through helm", self.name))),
+            false => Ok(()),
+        }
+    }
+
+    /// Chart URLs in index may be relative to repository.
+    fn resolve(&self, url: &str) -> String {
+        match url.contains("://") {
+            true => url.to_owned(),
+            false => format!("{}/{}", self.url.trim_end_matches('/'), url.trim_start_matches('/')),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryFile {
+    #[serde(default)]
+    repositories: Option<Vec<Repository>>,
+}
+
+/// Repositories configured in helm `repositories.yaml`, see `HELM_REPOSITORY_CONFIG`.
+pub fn load_repositories(path: &Path) -> Result<Vec<Repository>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(serde_yaml::from_str::<RepositoryFile>(content.as_str())?.repositories.unwrap_or_default()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Index of repository downloaded by `helm repo add` or `helm repo update`, see `HELM_REPOSITORY_CACHE`.
+pub fn load_cached_index(cache_dir: &Path, repository_name: &str) -> Result<RepositoryIndex> {
+    let path = cache_dir.join(format!("{}-index.yaml", repository_name));
+    match fs::read_to_string(&path) {
+        Ok(content) => RepositoryIndex::parse(content.as_str()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::CannotFetch(format!("index of {} is not downloaded yet, update repositories", repository_name))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Charts found in repository indexes and repositories which could not be searched.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SearchResult {
+    pub charts: Vec<ChartListItem>,
+    pub skipped: Vec<SkippedRepository>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SkippedRepository {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Single chart version from repository `index.yaml`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChartVersion {
+    pub name: String,
+    pub version: String,
+    #[serde(rename = "appVersion", default)]
+    pub app_version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub deprecated: Option<bool>,
+}
+
+impl ChartVersion {
+    fn list_item(&self, repository_name: &str) -> ChartListItem {
+        ChartListItem {
+            name: format!("{}/{}", repository_name, self.name),
+            version: self.version.clone(),
+            app_version: self.app_version.clone().unwrap_or_default(),
+            description: self.description.clone().unwrap_or_default(),
+        }
+    }
+
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.name.to_lowercase().contains(filter.as_str())
+            || self.keywords.iter().flatten().any(|k| k.to_lowercase().contains(filter.as_str()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RepositoryIndex {
+    #[serde(default)]
+    pub entries: BTreeMap<String, Vec<ChartVersion>>,
+}
+
+impl RepositoryIndex {
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Newest version of charts matching filter, like `helm search repo`.
+    pub fn search(&self, repository_name: &str, filter: &str) -> Vec<ChartListItem> {
+        self.entries.values()
+            .filter_map(|versions| versions.iter().max_by(|a, b| compare_versions(&a.version, &b.version)))
+            .filter(|chart| chart.matches(filter))
+            .map(|chart| chart.list_item(repository_name))
+            .collect()
+    }
+
+    /// All versions of a chart, newest first.
+    pub fn versions(&self, repository_name: &str, chart_name: &str) -> Vec<ChartListItem> {
+        let mut versions = self.entries.get(chart_name).cloned().unwrap_or_default();
+        versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        versions.iter().map(|c| c.list_item(repository_name)).collect()
+    }
+
+    /// Given version of a chart or the newest one.
+    pub fn find(&self, chart_name: &str, version: Option<&str>) -> Option<&ChartVersion> {
+        let versions = self.entries.get(chart_name)?.iter();
+        match version {
+            Some(version) => versions.into_iter().find(|c| c.version == version),
+            None => versions.max_by(|a, b| compare_versions(&a.version, &b.version)),
+        }
+    }
+}
+
+/// Semver ordering, versions which are not semver are older than any semver one.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Scheme, host and port of URL.
+fn origin(url: &str) -> &str {
+    let host_start = url.find("://").map(|i| i + 3).unwrap_or(0);
+    let host_end = url[host_start..].find('/').map(|i| host_start + i).unwrap_or(url.len());
+    &url[..host_end]
+}
+
+/// Reads chart repositories over HTTP(S) without helm.
+///
+/// Indexes are cached on disk and revalidated with ETag, so unchanged indexes are not downloaded again.
+#[derive(Clone)]
+pub struct IndexClient {
+    agent: ureq::Agent,
+    cache_dir: PathBuf,
+}
+
+impl IndexClient {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        IndexClient {
+            agent: ureq::AgentBuilder::new().build(),
+            cache_dir,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let builder = ureq::AgentBuilder::new();
+        self.agent = match timeout {
+            Some(timeout) => builder.timeout(timeout).build(),
+            None => builder.build(),
+        };
+        self
+    }
+
+    fn cache_file(&self, url: &str, extension: &str) -> PathBuf {
+        let name = url.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        self.cache_dir.join(format!("{}.{}", name, extension))
+    }
+
+    fn get(&self, repository: &Repository, url: &str) -> Result<ureq::Request> {
+        repository.check_tls()?;
+        let request = self.agent.get(url);
+        let same_origin = origin(url) == origin(repository.url.as_str());
+        Ok(match repository.authorization() {
+            Some(authorization) if same_origin || repository.pass_credentials_all => request.set("Authorization", authorization.as_str()),
+            _ => request,
+        })
+    }
+
+    fn post(&self, repository: &Repository, url: &str, content: &[u8]) -> Result<()> {
+        repository.check_tls()?;
+        let request = self.agent.post(url).set("Content-Type", "application/octet-stream");
+        let request = match repository.authorization() {
+            Some(authorization) => request.set("Authorization", authorization.as_str()),
+            None => request,
+        };
+        request.send_bytes(content)?;
+        Ok(())
+    }
+
+    pub fn fetch_index(&self, repository: &Repository) -> Result<RepositoryIndex> {
+        let url = repository.resolve("index.yaml");
+        let index_file = self.cache_file(url.as_str(), "yaml");
+        let etag_file = self.cache_file(url.as_str(), "etag");
+
+        let mut request = self.get(repository, url.as_str())?;
+        if let Ok(etag) = fs::read_to_string(&etag_file) {
+            if index_file.exists() {
+                request = request.set("If-None-Match", etag.as_str());
+            }
+        }
+        let response = request.call()?;
+        if response.status() == 304 {
+            log::debug!("Index of {} not modified", repository.name);
+            return RepositoryIndex::parse(fs::read_to_string(&index_file)?.as_str());
+        }
+
+        let etag = response.header("ETag").map(|e| e.to_owned());
+        let mut content = String::new();
+        response.into_reader().read_to_string(&mut content)?;
+        let index = RepositoryIndex::parse(content.as_str())?;
+
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(&index_file, content.as_bytes())?;
+        match etag {
+            Some(etag) => fs::write(&etag_file, etag.as_bytes())?,
+            None => if etag_file.exists() { fs::remove_file(&etag_file)? },
+        }
+        Ok(index)
+    }
+
+    /// Downloads chart archive into `destination` directory, returns path of the archive.
+    pub fn download_chart(&self, repository: &Repository, chart: &ChartVersion, destination: &Path) -> Result<PathBuf> {
+        let url = chart_url(repository, chart)?;
+        let mut content = vec![];
+        self.get(repository, url.as_str())?.call()?.into_reader().read_to_end(&mut content)?;
+        verify_digest(chart, content.as_slice())?;
+
+        fs::create_dir_all(destination)?;
+        let path = destination.join(archive_name(url.as_str(), chart));
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Downloads provenance file published next to chart archive, saved as `<archive>.prov` like helm does.
+    pub fn download_provenance(&self, repository: &Repository, chart: &ChartVersion, destination: &Path) -> Result<PathBuf> {
+        let url = format!("{}.prov", chart_url(repository, chart)?);
+        let response = self.get(repository, url.as_str())?.call().map_err(|e| match e {
+            ureq::Error::Status(404, _) => Error::ChartNotFound(format!("{} {} is not signed", chart.name, chart.version)),
+            e => e.into(),
+        })?;
+        let mut content = vec![];
+        response.into_reader().read_to_end(&mut content)?;
+
+        fs::create_dir_all(destination)?;
+        let path = destination.join(format!("{}.prov", archive_name(url.trim_end_matches(".prov"), chart)));
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Chart archive from cache, downloaded when missing or not matching the index digest. Published chart versions do not change.
+    pub fn fetch_chart(&self, repository: &Repository, chart: &ChartVersion) -> Result<PathBuf> {
+        let url = chart_url(repository, chart)?;
+        let cached = self.cache_file(repository.url.as_str(), "charts").join(archive_name(url.as_str(), chart));
+        if cached.exists() {
+            match verify_digest(chart, fs::read(&cached)?.as_slice()) {
+                Ok(()) => return Ok(cached),
+                Err(e) => log::warn!("Downloading cached chart again: {:?}", e),
+            }
+        }
+        self.download_chart(repository, chart, cached.parent().expect("Cannot happen"))
+    }
+
+    /// Uploads chart archive and its provenance file, when present, to ChartMuseum compatible repository.
+    pub fn upload_chart(&self, repository: &Repository, archive: &Path) -> Result<()> {
+        let api = format!("{}/api", repository.url.trim_end_matches('/'));
+        self.post(repository, format!("{}/charts", api).as_str(), fs::read(archive)?.as_slice())?;
+
+        let provenance = PathBuf::from(format!("{}.prov", archive.display()));
+        if provenance.exists() {
+            self.post(repository, format!("{}/prov", api).as_str(), fs::read(provenance)?.as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+fn chart_url(repository: &Repository, chart: &ChartVersion) -> Result<String> {
+    chart.urls.first()
+        .map(|u| repository.resolve(u))
+        .ok_or(Error::ChartNotFound(format!("{} {} has no download URL", chart.name, chart.version)))
+}
+
+/// Compares sha256 of archive with index digest, charts published without digest are accepted.
+fn verify_digest(chart: &ChartVersion, content: &[u8]) -> Result<()> {
+    let Some(expected) = chart.digest.as_deref().map(|d| d.trim_start_matches("sha256:")).filter(|d| !d.is_empty()) else {
+        return Ok(());
+    };
+    let actual = Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    match actual.eq_ignore_ascii_case(expected) {
+        true => Ok(()),
+        false => Err(Error::DigestMismatch(format!("{} {} has digest {}, index expects {}", chart.name, chart.version, actual, expected))),
+    }
+}
+
+fn archive_name(url: &str, chart: &ChartVersion) -> String {
+    url.rsplit('/').next()
+        .filter(|n| n.ends_with(".tgz"))
+        .map(|n| n.to_owned())
+        .unwrap_or(format!("{}-{}.tgz", chart.name, chart.version))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::{env, fs, thread};
+
+    use crate::helm::index::{Error, IndexClient, load_cached_index, load_repositories, Repository, RepositoryIndex};
+
+    /// Status, headers and body.
+    type Response = (&'static str, Vec<(&'static str, &'static str)>, Vec<u8>);
+
+    /// Serves given responses one per connection, sends received request headers back.
+    fn serve(responses: Vec<Response>) -> (String, mpsc::Receiver<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/charts", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (status, headers, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let request = BufReader::new(stream.try_clone().unwrap()).lines()
+                    .map(|l| l.unwrap())
+                    .take_while(|l| !l.is_empty())
+                    .collect::<Vec<String>>();
+                sender.send(request).unwrap();
+
+                let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, body.len());
+                for (name, value) in headers {
+                    response.push_str(format!("{}: {}\r\n", name, value).as_str());
+                }
+                stream.write_all(format!("{}\r\n", response).as_bytes()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    fn index_fixture() -> Vec<u8> {
+        fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/index/index.yaml")).unwrap()
+    }
+
+    #[test]
+    fn should_search_newest_chart_versions() {
+        let index = RepositoryIndex::parse(String::from_utf8(index_fixture()).unwrap().as_str()).unwrap();
+
+        let charts = index.search("bitnami", "NGINX");
+        assert_eq!(charts.len(), 1);
+        assert_eq!(charts[0].name, "bitnami/nginx");
+        assert_eq!(charts[0].version, "15.1.0");
+        assert_eq!(index.search("bitnami", "cache")[0].name, "bitnami/redis");
+
+        let versions = index.versions("bitnami", "nginx").into_iter().map(|c| c.version).collect::<Vec<String>>();
+        assert_eq!(versions, vec!["15.1.0", "15.0.2", "9.9.0"]);
+        assert_eq!(index.find("nginx", Some("9.9.0")).unwrap().urls[0], "https://mirror.example.com/nginx-9.9.0.tgz");
+    }
+
+    #[test]
+    fn should_revalidate_cached_index_with_etag() {
+        let (url, requests) = serve(vec![
+            ("200 OK", vec![("ETag", "\"v1\"")], index_fixture()),
+            ("304 Not Modified", vec![("ETag", "\"v1\"")], vec![]),
+        ]);
+        let cache_dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let client = IndexClient::new(cache_dir.clone());
+        let repository = Repository {
+            name: "bitnami".to_owned(),
+            url,
+            username: Some("user".to_owned()),
+            password: Some("secret".to_owned()),
+            ..Repository::default()
+        };
+
+        let fetched = client.fetch_index(&repository).unwrap();
+        let cached = client.fetch_index(&repository).unwrap();
+        fs::remove_dir_all(cache_dir).unwrap();
+
+        let first = requests.recv().unwrap();
+        let second = requests.recv().unwrap();
+        assert!(first[0].starts_with("GET /charts/index.yaml "));
+        assert!(first.contains(&"Authorization: Basic dXNlcjpzZWNyZXQ=".to_owned()));
+        assert!(second.iter().any(|h| h.eq_ignore_ascii_case("If-None-Match: \"v1\"")));
+        assert_eq!(fetched.entries.len(), cached.entries.len());
+    }
+
+    #[test]
+    fn should_download_chart_archive_once() {
+        let (url, requests) = serve(vec![("200 OK", vec![], b"archive".to_vec())]);
+        let cache_dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let repository = Repository { name: "bitnami".to_owned(), url, ..Repository::default() };
+        let index = RepositoryIndex::parse(String::from_utf8(index_fixture()).unwrap().as_str()).unwrap();
+        let client = IndexClient::new(cache_dir.clone());
+
+        let path = client.fetch_chart(&repository, index.find("nginx", None).unwrap()).unwrap();
+        let cached = client.fetch_chart(&repository, index.find("nginx", None).unwrap()).unwrap();
+        let content = fs::read(&path).unwrap();
+        fs::remove_dir_all(cache_dir).unwrap();
+
+        assert!(requests.recv().unwrap()[0].starts_with("GET /charts/nginx-15.1.0.tgz "));
+        assert!(path.ends_with("nginx-15.1.0.tgz"));
+        assert_eq!(path, cached);
+        assert_eq!(content, b"archive");
+    }
+
+    #[test]
+    fn should_reject_chart_archive_not_matching_digest() {
+        let (url, requests) = serve(vec![
+            ("200 OK", vec![], b"tampered".to_vec()),
+            ("200 OK", vec![], b"archive".to_vec()),
+        ]);
+        let cache_dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let repository = Repository { name: "bitnami".to_owned(), url, ..Repository::default() };
+        let index = RepositoryIndex::parse(String::from_utf8(index_fixture()).unwrap().as_str()).unwrap();
+        let chart = index.find("nginx", None).unwrap();
+        let client = IndexClient::new(cache_dir.clone());
+
+        let tampered = client.download_chart(&repository, chart, &cache_dir);
+        let stored = cache_dir.join("nginx-15.1.0.tgz").exists();
+        // corrupted cache is downloaded again
+        let cached = client.cache_file(repository.url.as_str(), "charts").join("nginx-15.1.0.tgz");
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, "corrupted").unwrap();
+        let path = client.fetch_chart(&repository, chart).unwrap();
+        let content = fs::read(&path).unwrap();
+        fs::remove_dir_all(cache_dir).unwrap();
+
+        assert!(matches!(tampered, Err(Error::DigestMismatch(_))));
+        assert!(!stored);
+        assert_eq!(requests.iter().take(2).count(), 2);
+        assert_eq!(content, b"archive");
+    }
+
+    #[test]
+    fn should_download_provenance_next_to_archive() {
+        let (url, requests) = serve(vec![
+            ("200 OK", vec![], b"-----BEGIN PGP SIGNED MESSAGE-----\n".to_vec()),
+            ("404 Not Found", vec![], vec![]),
+        ]);
+        let destination = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let repository = Repository { name: "bitnami".to_owned(), url, ..Repository::default() };
+        let index = RepositoryIndex::parse(String::from_utf8(index_fixture()).unwrap().as_str()).unwrap();
+        let client = IndexClient::new(destination.clone());
+
+        let path = client.download_provenance(&repository, index.find("nginx", None).unwrap(), &destination).unwrap();
+        let unsigned = client.download_provenance(&repository, index.find("nginx", None).unwrap(), &destination);
+        fs::remove_dir_all(destination).unwrap();
+
+        assert!(requests.recv().unwrap()[0].starts_with("GET /charts/nginx-15.1.0.tgz.prov "));
+        assert!(path.ends_with("nginx-15.1.0.tgz.prov"));
+        assert!(unsigned.is_err());
+    }
+
+    #[test]
+    fn should_upload_chart_to_chartmuseum() {
+        let (url, requests) = serve(vec![
+            ("201 Created", vec![], b"{\"saved\":true}".to_vec()),
+            ("409 Conflict", vec![], b"{\"error\":\"file already exists\"}".to_vec()),
+        ]);
+        let archive = env::temp_dir().join(format!("{}.tgz", uuid::Uuid::new_v4()));
+        fs::write(&archive, "chart").unwrap();
+        let client = IndexClient::new(env::temp_dir());
+        let repository = Repository { name: "".to_owned(), url, username: Some("ci".to_owned()), ..Repository::default() };
+
+        let uploaded = client.upload_chart(&repository, &archive);
+        let conflict = client.upload_chart(&repository, &archive);
+        fs::remove_file(&archive).unwrap();
+
+        let request = requests.recv().unwrap();
+        assert!(uploaded.is_ok());
+        assert!(request[0].starts_with("POST /charts/api/charts "));
+        assert!(request.contains(&"Authorization: Basic Y2k6".to_owned()));
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn should_load_repositories_with_credentials() {
+        let path = env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+        fs::write(&path, "apiVersion: \"\"\nrepositories:\n- name: internal\n  url: https://charts.example.com\n  username: ci\n  password: secret\n  pass_credentials_all: true\n  insecure_skip_tls_verify: false\n").unwrap();
+
+        let repositories = load_repositories(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(repositories[0].username, Some("ci".to_owned()));
+        assert!(repositories[0].pass_credentials_all);
+        assert!(load_repositories(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_not_fetch_directly_from_repository_with_tls_options() {
+        let repository = Repository {
+            name: "internal".to_owned(),
+            url: "https://127.0.0.1:1/charts".to_owned(),
+            ca_file: Some("/etc/ssl/internal-ca.pem".to_owned()),
+            ..Repository::default()
+        };
+        let client = IndexClient::new(env::temp_dir());
+
+        let result = client.fetch_index(&repository);
+
+        assert!(matches!(result, Err(Error::CannotFetch(e)) if e.contains("TLS options")));
+    }
+
+    #[test]
+    fn should_search_index_cached_by_helm() {
+        let cache_dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("bitnami-index.yaml"), index_fixture()).unwrap();
+
+        let index = load_cached_index(&cache_dir, "bitnami").unwrap();
+        let missing = load_cached_index(&cache_dir, "other");
+        fs::remove_dir_all(cache_dir).unwrap();
+
+        assert_eq!(index.search("bitnami", "nginx")[0].version, "15.1.0");
+        assert!(matches!(missing, Err(Error::CannotFetch(e)) if e.contains("update repositories")));
+    }
+}
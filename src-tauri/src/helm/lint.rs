@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LintRequest {
+    /// Local chart directory or `.tgz` archive.
+    #[serde(rename = "chartPath")]
+    pub chart_path: String,
+    #[serde(rename = "valuesFiles", default)]
+    pub values_files: Vec<String>,
+    /// Fails also on warnings.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    #[serde(rename = "info")]
+    Info,
+    #[serde(rename = "warning")]
+    Warning,
+    #[serde(rename = "error")]
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub severity: Severity,
+    /// File or directory relative to chart root, e.g. `templates/`.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LintResult {
+    pub passed: bool,
+    pub findings: Vec<LintFinding>,
+}
+
+/// Parses `[SEVERITY] path: message` lines printed by `helm lint`, indented lines continue the previous message.
+pub fn parse(output: &str) -> Vec<LintFinding> {
+    let mut findings: Vec<LintFinding> = vec![];
+    for line in output.lines() {
+        let finding = line.strip_prefix('[')
+            .and_then(|l| l.split_once("] "))
+            .and_then(|(severity, rest)| {
+                let severity = match severity {
+                    "INFO" => Severity::Info,
+                    "WARNING" => Severity::Warning,
+                    "ERROR" => Severity::Error,
+                    _ => return None,
+                };
+                let (path, message) = match rest.split_once(": ") {
+                    Some((path, message)) if !path.contains(' ') => (Some(path.to_owned()), message.to_owned()),
+                    _ => (None, rest.to_owned()),
+                };
+                Some(LintFinding { severity, path, message })
+            });
+
+        match (finding, findings.last_mut()) {
+            (Some(finding), _) => findings.push(finding),
+            (None, Some(last)) if line.starts_with(char::is_whitespace) && !line.trim().is_empty() => {
+                last.message.push('\n');
+                last.message.push_str(line.trim());
+            }
+            _ => {}
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::lint::{parse, Severity};
+
+    #[test]
+    fn should_parse_lint_findings() {
+        let output = "==> Linting charts/app\n\
+                      [INFO] Chart.yaml: icon is recommended\n\
+                      [WARNING] templates/ingress.yaml: networking.k8s.io/v1beta1 Ingress is deprecated in v1.19+\n\
+                      [ERROR] templates/: template: app/templates/service.yaml:4:18: executing \"app/templates/service.yaml\" at <.Values.service.port>: nil pointer\n\
+                      [ERROR] values.yaml: unable to parse YAML\n\
+                      \terror converting YAML to JSON: yaml: line 3: mapping values are not allowed in this context\n\
+                      [ERROR] chart metadata is missing these dependencies: redis\n\
+                      \n\
+                      1 chart(s) linted, 1 chart(s) failed\n";
+
+        let findings = parse(output);
+
+        assert_eq!(findings.len(), 5);
+        assert_eq!(findings[0].severity, Severity::Info);
+        assert_eq!(findings[0].path, Some("Chart.yaml".to_owned()));
+        assert_eq!(findings[2].path, Some("templates/".to_owned()));
+        assert!(findings[2].message.starts_with("template: app/templates/service.yaml:4:18"));
+        assert!(findings[3].message.ends_with("\nerror converting YAML to JSON: yaml: line 3: mapping values are not allowed in this context"));
+        assert_eq!(findings[4].path, None);
+    }
+}
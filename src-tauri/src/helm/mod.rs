@@ -1,4 +1,17 @@
 pub mod cmd;
 pub mod api;
+pub mod bundle;
+pub mod helmfile;
+pub mod gitops;
+pub mod batch;
+pub mod outdated;
+pub mod watcher;
+pub mod discovery;
+pub mod index;
+pub mod archive;
+pub mod dependency;
+pub mod lint;
+pub mod package;
+pub mod verify;
 pub use api::Api;
 pub use cmd::Cmd;
\ No newline at end of file
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::helm::cmd::{ChartListItem, ChartReference, Release};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum UpgradeLevel {
+    #[serde(rename = "patch")]
+    Patch,
+    #[serde(rename = "minor")]
+    Minor,
+    #[serde(rename = "major")]
+    Major,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReleaseUpdate {
+    pub release: String,
+    pub namespace: String,
+    #[serde(rename = "chartName")]
+    pub chart_name: String,
+    pub repository: Option<String>,
+    #[serde(rename = "currentVersion")]
+    pub current_version: String,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: Option<String>,
+    #[serde(rename = "currentAppVersion")]
+    pub current_app_version: String,
+    #[serde(rename = "latestAppVersion")]
+    pub latest_app_version: Option<String>,
+    /// Empty when release is up to date or versions are not semver.
+    #[serde(rename = "upgradeLevel")]
+    pub upgrade_level: Option<UpgradeLevel>,
+    #[serde(rename = "appVersionChanged")]
+    pub app_version_changed: bool,
+    /// Repositories the release could have been installed from, set when there is more than one.
+    #[serde(rename = "ambiguousRepositories")]
+    pub ambiguous_repositories: Vec<String>,
+}
+
+/// Compares installed releases with the newest chart versions found in the repositories they were installed from.
+pub fn check(releases: &[Release], charts: &[ChartListItem]) -> Vec<ReleaseUpdate> {
+    releases.iter()
+        .filter_map(|release| {
+            let chart = release.chart_reference.clone()?;
+            let repositories = source_repositories(charts, &chart);
+            let latest = match repositories.as_slice() {
+                [repository] => newest_chart(charts, repository, chart.name.as_str()),
+                _ => None,
+            };
+
+            let upgrade_level = latest.and_then(|(_, c)| upgrade_level(chart.version.as_str(), c.version.as_str()));
+            let app_version_changed = upgrade_level.is_some()
+                && latest.map(|(_, c)| c.app_version != release.app_version).unwrap_or(false);
+
+            Some(ReleaseUpdate {
+                release: release.name.clone(),
+                namespace: release.namespace.clone(),
+                chart_name: chart.name,
+                repository: latest.map(|(repo, _)| repo.to_owned()),
+                current_version: chart.version,
+                latest_version: latest.map(|(_, c)| c.version.clone()),
+                current_app_version: release.app_version.clone(),
+                latest_app_version: latest.map(|(_, c)| c.app_version.clone()),
+                upgrade_level,
+                app_version_changed,
+                ambiguous_repositories: if repositories.len() > 1 { repositories } else { vec![] },
+            })
+        })
+        .collect()
+}
+
+/// Returns how big the upgrade from `current` to `latest` is, `None` when there is nothing newer.
+pub fn upgrade_level(current: &str, latest: &str) -> Option<UpgradeLevel> {
+    let current = parse_version(current)?;
+    let latest = parse_version(latest)?;
+    if latest <= current {
+        return None;
+    }
+    if latest.major != current.major {
+        Some(UpgradeLevel::Major)
+    } else if latest.minor != current.minor {
+        Some(UpgradeLevel::Minor)
+    } else {
+        Some(UpgradeLevel::Patch)
+    }
+}
+
+/// Repositories serving installed chart version, all repositories carrying the chart when none has that version.
+pub fn source_repositories(charts: &[ChartListItem], chart: &ChartReference) -> Vec<String> {
+    let carrying = |version: Option<&str>| {
+        let mut repositories = charts.iter()
+            .filter(|c| version.map_or(true, |v| c.version == v))
+            .filter_map(|c| c.name.split_once('/'))
+            .filter(|(_, name)| *name == chart.name)
+            .map(|(repo, _)| repo.to_owned())
+            .collect::<Vec<String>>();
+        repositories.sort();
+        repositories.dedup();
+        repositories
+    };
+    let repositories = carrying(Some(chart.version.as_str()));
+    if repositories.is_empty() {
+        carrying(None)
+    } else {
+        repositories
+    }
+}
+
+/// Chart with the highest version in given repository, together with repository name.
+fn newest_chart<'a>(charts: &'a [ChartListItem], repository: &str, chart_name: &str) -> Option<(&'a str, &'a ChartListItem)> {
+    charts.iter()
+        .filter_map(|c| {
+            let (repo, name) = c.name.split_once('/')?;
+            let version = parse_version(c.version.as_str())?;
+            (repo == repository && name == chart_name).then_some((repo, c, version))
+        })
+        .max_by(|(_, _, a), (_, _, b)| a.cmp(b))
+        .map(|(repo, c, _)| (repo, c))
+}
+
+pub fn parse_version(version: &str) -> Option<semver::Version> {
+    semver::Version::parse(version.trim_start_matches('v')).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::cmd::{ChartListItem, Release};
+    use crate::helm::outdated::{check, upgrade_level, UpgradeLevel};
+
+    #[test]
+    fn should_detect_upgrade_level() {
+        assert_eq!(upgrade_level("1.2.3", "1.2.4"), Some(UpgradeLevel::Patch));
+        assert_eq!(upgrade_level("1.2.3", "1.3.0"), Some(UpgradeLevel::Minor));
+        assert_eq!(upgrade_level("1.2.3", "2.0.0"), Some(UpgradeLevel::Major));
+        assert_eq!(upgrade_level("1.2.3-rc.1", "1.2.3"), Some(UpgradeLevel::Patch));
+        assert_eq!(upgrade_level("v1.10.0", "v1.9.0"), None);
+        assert_eq!(upgrade_level("1.2.3", "1.2.3"), None);
+        assert_eq!(upgrade_level("latest", "1.2.3"), None);
+    }
+
+    #[test]
+    fn should_find_newest_version_in_source_repository() {
+        let releases = vec![release("web", "nginx-ingress-4.7.1", "1.8.1"), release("db", "postgresql-12.5.0", "15.3.0")];
+        let charts = vec![
+            chart("ingress/nginx-ingress", "4.10.0", "1.10.0"),
+            chart("ingress/nginx-ingress", "4.7.1", "1.8.1"),
+            chart("mirror/nginx-ingress", "5.0.0", "1.11.0"),
+            chart("bitnami/postgresql", "12.5.0", "15.3.0"),
+        ];
+
+        let result = check(&releases, &charts);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].repository, Some("ingress".to_owned()));
+        assert_eq!(result[0].latest_version, Some("4.10.0".to_owned()));
+        assert_eq!(result[0].upgrade_level, Some(UpgradeLevel::Minor));
+        assert!(result[0].app_version_changed);
+        assert!(result[0].ambiguous_repositories.is_empty());
+        assert_eq!(result[1].upgrade_level, None);
+        assert!(!result[1].app_version_changed);
+    }
+
+    #[test]
+    fn should_report_ambiguous_source_repository() {
+        let releases = vec![release("web", "nginx-ingress-4.7.1", "1.8.1")];
+        let charts = vec![
+            chart("ingress/nginx-ingress", "4.10.0", "1.10.0"),
+            chart("ingress/nginx-ingress", "4.7.1", "1.8.1"),
+            chart("mirror/nginx-ingress", "4.7.1", "1.8.1"),
+        ];
+
+        let result = check(&releases, &charts);
+
+        assert_eq!(result[0].repository, None);
+        assert_eq!(result[0].latest_version, None);
+        assert_eq!(result[0].upgrade_level, None);
+        assert_eq!(result[0].ambiguous_repositories, vec!["ingress".to_owned(), "mirror".to_owned()]);
+    }
+
+    fn release(name: &str, chart: &str, app_version: &str) -> Release {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "namespace": "default",
+            "revision": "1",
+            "updated": "2023-05-01 10:00:00.000000000 +0000 UTC",
+            "status": "deployed",
+            "chart": chart,
+            "app_version": app_version,
+        })).unwrap()
+    }
+
+    fn chart(name: &str, version: &str, app_version: &str) -> ChartListItem {
+        ChartListItem {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            app_version: app_version.to_owned(),
+            description: "".to_owned(),
+        }
+    }
+}
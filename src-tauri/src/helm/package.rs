@@ -0,0 +1,127 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const OCI_SCHEME: &'static str = "oci://";
+
+/// Signs package with a PGP key, see `helm package --sign`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignOptions {
+    /// Name of the key in keyring.
+    pub key: String,
+    pub keyring: String,
+    #[serde(rename = "passphraseFile")]
+    pub passphrase_file: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PackageRequest {
+    #[serde(rename = "chartPath")]
+    pub chart_path: String,
+    /// Directory for the archive, current directory when not set.
+    pub destination: Option<String>,
+    /// Overrides version from `Chart.yaml`.
+    pub version: Option<String>,
+    #[serde(rename = "appVersion")]
+    pub app_version: Option<String>,
+    pub sign: Option<SignOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageResult {
+    pub path: String,
+    /// `sha256:` digest of the archive, as in repository index.
+    pub digest: String,
+    /// Provenance file of signed package.
+    pub provenance: Option<String>,
+}
+
+impl PackageResult {
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let provenance = Path::new(format!("{}.prov", path.display()).as_str()).to_path_buf();
+        Ok(PackageResult {
+            path: path.to_string_lossy().to_string(),
+            digest: digest(path)?,
+            provenance: provenance.exists().then(|| provenance.to_string_lossy().to_string()),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PublishRequest {
+    /// Packaged chart archive.
+    pub archive: String,
+    /// ChartMuseum compatible repository, or OCI registry as `oci://registry/path` using `helm registry login` credentials.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishResult {
+    pub package: PackageResult,
+    /// Repository URL or pushed OCI reference.
+    pub location: String,
+    /// Digest of OCI manifest reported by registry.
+    #[serde(rename = "manifestDigest")]
+    pub manifest_digest: Option<String>,
+}
+
+pub fn digest(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    let hash = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    Ok(format!("sha256:{}", hash))
+}
+
+/// Archive path from `Successfully packaged chart and saved it to: <path>`.
+pub fn parse_package_output(output: &str) -> Option<String> {
+    output.lines()
+        .find_map(|line| line.split_once("saved it to:"))
+        .map(|(_, path)| path.trim().to_owned())
+}
+
+/// Reference and digest from `Pushed: <reference>` and `Digest: <digest>` lines of `helm push`.
+pub fn parse_push_output(output: &str) -> (Option<String>, Option<String>) {
+    let value = |prefix: &str| output.lines()
+        .find_map(|line| line.trim().strip_prefix(prefix))
+        .map(|v| v.trim().to_owned());
+    (value("Pushed:"), value("Digest:"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::helm::package::{digest, parse_package_output, parse_push_output};
+
+    #[test]
+    fn should_compute_archive_digest() {
+        let path = env::temp_dir().join(format!("{}.tgz", uuid::Uuid::new_v4()));
+        fs::write(&path, "chart").unwrap();
+
+        let digest = digest(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(digest, "sha256:cc57fc1903e444cf6a726490b43b27ee9f87facc037f86872201847c565b45fb");
+    }
+
+    #[test]
+    fn should_parse_helm_output() {
+        assert_eq!(parse_package_output("Successfully packaged chart and saved it to: /charts/app-1.2.0.tgz\n"), Some("/charts/app-1.2.0.tgz".to_owned()));
+        let (reference, digest) = parse_push_output("Pushed: registry.example.com/charts/app:1.2.0\nDigest: sha256:4a5c1d\n");
+        assert_eq!(reference, Some("registry.example.com/charts/app:1.2.0".to_owned()));
+        assert_eq!(digest, Some("sha256:4a5c1d".to_owned()));
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationStatus {
+    #[serde(rename = "verified")]
+    Verified,
+    /// Chart has no provenance file.
+    #[serde(rename = "unsigned")]
+    Unsigned,
+    /// Signature is made by unknown key or chart hash does not match.
+    #[serde(rename = "invalid")]
+    Invalid,
+}
+
+/// Result of `helm verify` for chart archive and its `.prov` file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerificationResult {
+    pub status: VerificationStatus,
+    /// Identity of the signing key, e.g. `Helm Testing <helm-testing@helm.sh>`.
+    pub signer: Option<String>,
+    pub fingerprint: Option<String>,
+    /// `sha256:` hash of the archive confirmed by signature.
+    pub hash: Option<String>,
+    /// Reason of failed verification as reported by helm.
+    pub message: Option<String>,
+}
+
+impl VerificationResult {
+    pub fn is_verified(&self) -> bool {
+        self.status == VerificationStatus::Verified
+    }
+}
+
+/// Parses `Signed by:`, `Using Key With Fingerprint:` and `Chart Hash Verified:` lines of successful `helm verify`.
+pub fn parse(output: &str) -> VerificationResult {
+    let value = |prefix: &str| output.lines()
+        .find_map(|line| line.trim().strip_prefix(prefix))
+        .map(|v| v.trim().to_owned());
+    VerificationResult {
+        status: VerificationStatus::Verified,
+        signer: value("Signed by:"),
+        fingerprint: value("Using Key With Fingerprint:"),
+        hash: value("Chart Hash Verified:"),
+        message: None,
+    }
+}
+
+/// Failed verification from `Error: ...` printed by `helm verify`.
+pub fn parse_failure(error: &str) -> VerificationResult {
+    let message = error.trim().trim_start_matches("Error:").trim().to_owned();
+    let status = match message.contains("provenance file") {
+        true => VerificationStatus::Unsigned,
+        false => VerificationStatus::Invalid,
+    };
+    VerificationResult {
+        status,
+        signer: None,
+        fingerprint: None,
+        hash: None,
+        message: Some(message),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::verify::{parse, parse_failure, VerificationStatus};
+
+    #[test]
+    fn should_parse_verified_chart() {
+        let output = "Signed by: Helm Testing (This key should only be used for testing. DO NOT TRUST.) <helm-testing@helm.sh>\n\
+                      Using Key With Fingerprint: 5E615389B53CA37F0EE60BD3843BBF981FC18762\n\
+                      Chart Hash Verified: sha256:e5ef611620fb97704d8751c16bab17fedb68883bfb0edc76f78a70e9173f9b55\n";
+
+        let result = parse(output);
+
+        assert!(result.is_verified());
+        assert_eq!(result.signer, Some("Helm Testing (This key should only be used for testing. DO NOT TRUST.) <helm-testing@helm.sh>".to_owned()));
+        assert_eq!(result.fingerprint, Some("5E615389B53CA37F0EE60BD3843BBF981FC18762".to_owned()));
+        assert_eq!(result.hash, Some("sha256:e5ef611620fb97704d8751c16bab17fedb68883bfb0edc76f78a70e9173f9b55".to_owned()));
+    }
+
+    #[test]
+    fn should_parse_verification_failures() {
+        let unsigned = parse_failure("Error: could not load provenance file /charts/app-1.2.0.tgz.prov: stat /charts/app-1.2.0.tgz.prov: no such file or directory\n");
+        let unknown = parse_failure("Error: openpgp: signature made by unknown entity\n");
+
+        assert_eq!(unsigned.status, VerificationStatus::Unsigned);
+        assert_eq!(unknown.status, VerificationStatus::Invalid);
+        assert_eq!(unknown.message, Some("openpgp: signature made by unknown entity".to_owned()));
+    }
+}
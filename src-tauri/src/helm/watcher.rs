@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::executor::Executor;
+use crate::helm;
+use crate::helm::cmd::{Release, ReleaseStatus};
+
+pub const DEFAULT_INTERVAL_SECONDS: u64 = 10;
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum ReleaseChange {
+    #[serde(rename = "added")]
+    Added {
+        release: Release,
+    },
+    #[serde(rename = "removed")]
+    Removed {
+        release: Release,
+    },
+    #[serde(rename = "upgraded")]
+    Upgraded {
+        #[serde(rename = "previousRevision")]
+        previous_revision: u32,
+        release: Release,
+    },
+    #[serde(rename = "statusChanged")]
+    StatusChanged {
+        #[serde(rename = "previousStatus")]
+        previous_status: ReleaseStatus,
+        release: Release,
+    },
+}
+
+/// Changes between two successive `helm list` results.
+///
+/// New revision is reported as upgrade (it covers rollbacks too), status change
+/// is reported only when revision stays the same.
+pub fn diff(previous: &[Release], current: &[Release]) -> Vec<ReleaseChange> {
+    let key = |r: &Release| (r.namespace.clone(), r.name.clone());
+    let previous_by_key = previous.iter()
+        .map(|r| (key(r), r))
+        .collect::<HashMap<(String, String), &Release>>();
+    let current_by_key = current.iter()
+        .map(|r| (key(r), r))
+        .collect::<HashMap<(String, String), &Release>>();
+
+    let mut changes = vec![];
+    for release in current.iter() {
+        match previous_by_key.get(&key(release)) {
+            None => changes.push(ReleaseChange::Added { release: release.clone() }),
+            Some(old) if old.revision != release.revision => changes.push(ReleaseChange::Upgraded {
+                previous_revision: old.revision,
+                release: release.clone(),
+            }),
+            Some(old) if old.status != release.status => changes.push(ReleaseChange::StatusChanged {
+                previous_status: old.status,
+                release: release.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for release in previous.iter() {
+        if !current_by_key.contains_key(&key(release)) {
+            changes.push(ReleaseChange::Removed { release: release.clone() });
+        }
+    }
+    changes
+}
+
+/// Polls `helm list` in given kube context until `stop` is set.
+///
+/// The first result is a baseline, afterwards `on_change` is called for every difference
+/// and `on_error` when helm cannot be queried (the previous baseline is kept then).
+pub fn watch<C, E>(executor: &mut dyn Executor, helm_binary: Option<String>, kube_context: Option<String>, interval: Duration, stop: Arc<AtomicBool>, on_change: C, on_error: E)
+    where
+        C: Fn(ReleaseChange),
+        E: Fn(String),
+{
+    let mut previous: Option<Vec<Release>> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let mut api = helm::Api::new(executor)
+            .with_helm_binary(helm_binary.clone())
+            .with_kube_context(kube_context.clone());
+        match api.list() {
+            Ok(current) => {
+                if let Some(previous) = previous.as_ref() {
+                    diff(previous, &current).into_iter().for_each(&on_change);
+                }
+                previous = Some(current);
+            }
+            Err(e) => on_error(e.to_string())
+        }
+
+        let started = Instant::now();
+        while started.elapsed() < interval && !stop.load(Ordering::Relaxed) {
+            thread::sleep(STOP_CHECK_INTERVAL.min(interval));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::helm::cmd::{Release, ReleaseStatus};
+    use crate::helm::watcher::{diff, ReleaseChange};
+
+    #[test]
+    fn should_detect_release_changes() {
+        let previous = vec![
+            release("web", 1, "deployed"),
+            release("db", 3, "deployed"),
+            release("cache", 1, "pending-install"),
+        ];
+        let current = vec![
+            release("web", 2, "deployed"),
+            release("cache", 1, "failed"),
+            release("queue", 1, "deployed"),
+        ];
+
+        let changes = diff(&previous, &current);
+
+        assert_eq!(changes.len(), 4);
+        assert!(matches!(&changes[0], ReleaseChange::Upgraded { previous_revision: 1, release } if release.name == "web"));
+        assert!(matches!(&changes[1], ReleaseChange::StatusChanged { previous_status: ReleaseStatus::PendingInstall, release } if release.name == "cache"));
+        assert!(matches!(&changes[2], ReleaseChange::Added { release } if release.name == "queue"));
+        assert!(matches!(&changes[3], ReleaseChange::Removed { release } if release.name == "db"));
+    }
+
+    #[test]
+    fn should_not_report_anything_when_nothing_changed() {
+        let releases = vec![release("web", 1, "deployed")];
+        assert!(diff(&releases, &vec![release("web", 1, "deployed")]).is_empty());
+    }
+
+    fn release(name: &str, revision: u32, status: &str) -> Release {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "namespace": "default",
+            "revision": revision.to_string(),
+            "updated": "2023-05-01 10:00:00.000000000 +0000 UTC",
+            "status": status,
+            "chart": "nginx-15.0.0",
+            "app_version": "1.25.0",
+        })).unwrap()
+    }
+}
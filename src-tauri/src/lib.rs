@@ -0,0 +1,9 @@
+pub mod helm;
+pub mod executor;
+pub mod audit;
+pub mod redaction;
+pub mod logging;
+pub mod settings;
+pub mod profile;
+/// Recording and replaying executors, public so integration tests in `tests/` can drive helm code with them.
+pub mod replay;
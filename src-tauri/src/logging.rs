@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+const LOG_FILE_NAME: &'static str = "helmik.log";
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 5;
+const RECENT_LINES_CAPACITY: usize = 1000;
+/// Overrides default log level, e.g. `HELMIK_LOG=trace`.
+const LEVEL_ENV: &'static str = "HELMIK_LOG";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogLine {
+    fn format(&self) -> String {
+        format!("{} {:<5} [{}] {}", self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3f%:z"), self.level, self.target, self.message)
+    }
+}
+
+/// Most recent log lines kept in memory for the diagnostics panel.
+#[derive(Clone, Default)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+}
+
+impl RecentLogs {
+    fn push(&self, line: LogLine) {
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == RECENT_LINES_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    /// Up to `limit` newest lines at `level` or more severe, oldest first.
+    pub fn get(&self, limit: usize, level: Level) -> Vec<LogLine> {
+        let lines = match self.lines.lock() {
+            Ok(lines) => lines,
+            Err(_) => return vec![],
+        };
+        let mut result = lines.iter()
+            .rev()
+            .filter(|l| l.level.parse::<Level>().map(|l| l <= level).unwrap_or(true))
+            .take(limit)
+            .cloned()
+            .collect::<Vec<LogLine>>();
+        result.reverse();
+        result
+    }
+}
+
+/// Writes log records to size-rotated files (`helmik.log`, `helmik.log.1`, ...) and keeps recent ones in memory.
+pub struct Logger {
+    level: LevelFilter,
+    dir: PathBuf,
+    file: Mutex<Option<File>>,
+    recent: RecentLogs,
+}
+
+impl Logger {
+    pub fn new(dir: PathBuf, level: LevelFilter) -> Self {
+        Logger {
+            level,
+            dir,
+            file: Mutex::new(None),
+            recent: RecentLogs::default(),
+        }
+    }
+
+    pub fn recent(&self) -> RecentLogs {
+        self.recent.clone()
+    }
+
+    fn write_line(&self, line: &str) -> std::io::Result<()> {
+        let mut file = self.file.lock().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        let path = self.dir.join(LOG_FILE_NAME);
+
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if size >= MAX_FILE_SIZE {
+            *file = None;
+            self.rotate()?;
+        }
+        if file.is_none() {
+            fs::create_dir_all(&self.dir)?;
+            *file = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+        }
+        if let Some(file) = file.as_mut() {
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        let rotated = |i: usize| self.dir.join(format!("{}.{}", LOG_FILE_NAME, i));
+        let oldest = rotated(MAX_ROTATED_FILES - 1);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for i in (1..MAX_ROTATED_FILES - 1).rev() {
+            if rotated(i).exists() {
+                fs::rename(rotated(i), rotated(i + 1))?;
+            }
+        }
+        fs::rename(self.dir.join(LOG_FILE_NAME), rotated(1))
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = LogLine {
+            timestamp: chrono::Local::now(),
+            level: record.level().to_string(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        };
+        if let Err(e) = self.write_line(line.format().as_str()) {
+            eprintln!("Cannot write log file: {}", e);
+        }
+        self.recent.push(line);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Installs global logger writing to `dir`, returns handle to recent log lines.
+pub fn init(dir: PathBuf) -> RecentLogs {
+    let default_level = match cfg!(debug_assertions) {
+        true => LevelFilter::Debug,
+        false => LevelFilter::Info
+    };
+    let level = std::env::var(LEVEL_ENV)
+        .ok()
+        .and_then(|l| l.parse::<LevelFilter>().ok())
+        .unwrap_or(default_level);
+
+    let logger = Box::new(Logger::new(dir, level));
+    let recent = logger.recent();
+    match log::set_boxed_logger(logger) {
+        Ok(_) => log::set_max_level(level),
+        Err(e) => eprintln!("Cannot initialize logging: {}", e)
+    }
+    recent
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    use crate::logging::{LOG_FILE_NAME, Logger, MAX_FILE_SIZE, MAX_ROTATED_FILES};
+
+    fn log(logger: &Logger, level: Level, message: &str) {
+        logger.log(&Record::builder()
+            .level(level)
+            .target("test")
+            .args(format_args!("{}", message))
+            .build());
+    }
+
+    #[test]
+    fn should_keep_recent_lines_filtered_by_level() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let logger = Logger::new(dir.clone(), LevelFilter::Info);
+
+        log(&logger, Level::Debug, "skipped");
+        log(&logger, Level::Info, "first");
+        log(&logger, Level::Warn, "second");
+        log(&logger, Level::Error, "third");
+
+        let all = logger.recent().get(10, Level::Trace);
+        let warnings = logger.recent().get(10, Level::Warn);
+        let last = logger.recent().get(1, Level::Trace);
+        let file = fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(all.iter().map(|l| l.message.as_str()).collect::<Vec<&str>>(), vec!["first", "second", "third"]);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(last[0].message, "third");
+        assert_eq!(file.lines().count(), 3);
+        assert!(file.contains("WARN  [test] second"));
+    }
+
+    #[test]
+    fn should_rotate_log_files() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let logger = Logger::new(dir.clone(), LevelFilter::Info);
+        let big_message = "x".repeat(MAX_FILE_SIZE as usize);
+
+        for _ in 0..MAX_ROTATED_FILES + 2 {
+            log(&logger, Level::Info, big_message.as_str());
+        }
+
+        let files = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(files, MAX_ROTATED_FILES);
+    }
+}
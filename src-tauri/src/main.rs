@@ -4,15 +4,46 @@ windows_subsystem = "windows"
 )]
 
 
-mod helm;
-pub mod executor;
+use tauri::Manager;
+
+use helmik::{audit, executor, helm, logging, profile, settings};
+
 pub mod commands;
 
 
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            let log_dir = app.path_resolver()
+                .app_log_dir()
+                .expect("Cannot resolve application log directory");
+            let logs = logging::init(log_dir);
+
+            let config_dir = app.path_resolver()
+                .app_config_dir()
+                .expect("Cannot resolve application config directory");
+            let settings = settings::SettingsStore::load(config_dir.join("settings.json"));
+
+            let data_dir = app.path_resolver()
+                .app_data_dir()
+                .expect("Cannot resolve application data directory");
+            let audit_log = audit::AuditLog::new(data_dir.join("audit.jsonl"));
+
+            let cache_dir = app.path_resolver()
+                .app_cache_dir()
+                .expect("Cannot resolve application cache directory");
+            let index = helm::index::IndexClient::new(cache_dir.join("index"));
+
+            app.manage(commands::state::AppState::new(settings, audit_log, logs, index));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::releases::list_releases,
+            commands::releases::export_release,
+            commands::releases::import_release,
+            commands::releases::export_gitops,
+            commands::releases::batch_operation,
+            commands::releases::check_release_updates,
             commands::repo::repos,
             commands::repo::delete_repository,
             commands::repo::add_repository,
@@ -21,9 +52,40 @@ fn main() {
             commands::charts::install_chart,
             commands::charts::find_charts_in_repo,
             commands::charts::get_chart_details,
+            commands::charts::get_chart_versions,
+            commands::charts::pull_chart,
+            commands::charts::verify_chart,
+            commands::charts::template_chart,
+            commands::charts::list_chart_dependencies,
+            commands::charts::update_chart_dependencies,
+            commands::charts::build_chart_dependencies,
+            commands::charts::resolve_subcharts,
+            commands::charts::lint_chart,
+            commands::charts::package_chart,
+            commands::charts::publish_chart,
             commands::charts::uninstall_chart,
             commands::charts::get_values,
-            commands::others::version
+            commands::helmfile::load_helmfile,
+            commands::helmfile::apply_helmfile,
+            commands::helmfile::export_helmfile,
+            commands::watcher::start_release_watcher,
+            commands::watcher::stop_release_watcher,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
+            commands::profiles::create_profile,
+            commands::profiles::update_profile,
+            commands::profiles::delete_profile,
+            commands::profiles::switch_profile,
+            commands::audit::query_audit_log,
+            commands::audit::export_audit_log,
+            commands::setup::discover_helm_binaries,
+            commands::setup::set_helm_binary,
+            commands::setup::check_helm_setup,
+            commands::others::version,
+            commands::others::helm_env,
+            commands::others::running_operations,
+            commands::others::cancel_operation,
+            commands::others::recent_logs
         ]
         )
         .run(tauri::generate_context!())
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PROFILE: &'static str = "default";
+
+/// Named workspace with its own kubeconfig, helm directories and defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Directory holding helm config, cache and data of this profile, user ones are used when not set.
+    #[serde(rename = "helmHome")]
+    pub helm_home: Option<String>,
+    pub kubeconfig: Option<String>,
+    #[serde(rename = "kubeContext")]
+    pub kube_context: Option<String>,
+    #[serde(rename = "defaultNamespace")]
+    pub default_namespace: Option<String>,
+    /// Passed to helm, e.g. `HELM_DRIVER` or `HTTPS_PROXY`.
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    /// Keyring with public keys trusted for chart verification, helm default `~/.gnupg/pubring.gpg` when not set.
+    pub keyring: Option<String>,
+    /// Only charts with verified provenance can be installed, meant for production clusters.
+    #[serde(rename = "requireSignedCharts", default)]
+    pub require_signed_charts: bool,
+}
+
+impl Profile {
+    pub fn new(name: String) -> Self {
+        Profile {
+            name,
+            helm_home: None,
+            kubeconfig: None,
+            kube_context: None,
+            default_namespace: None,
+            environment: BTreeMap::new(),
+            keyring: None,
+            require_signed_charts: false,
+        }
+    }
+
+    /// Environment for helm, explicitly set variables take precedence over ones derived from profile.
+    pub fn environment(&self) -> BTreeMap<String, String> {
+        let mut environment = BTreeMap::new();
+        if let Some(helm_home) = self.helm_home.as_ref() {
+            let dir = |name: &str| Path::new(helm_home).join(name).to_string_lossy().to_string();
+            environment.insert("HELM_CONFIG_HOME".to_owned(), dir("config"));
+            environment.insert("HELM_CACHE_HOME".to_owned(), dir("cache"));
+            environment.insert("HELM_DATA_HOME".to_owned(), dir("data"));
+        }
+        if let Some(kubeconfig) = self.kubeconfig.as_ref() {
+            environment.insert("KUBECONFIG".to_owned(), kubeconfig.clone());
+        }
+        environment.extend(self.environment.clone());
+        environment
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::new(DEFAULT_PROFILE.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::profile::Profile;
+
+    #[test]
+    fn should_isolate_helm_directories() {
+        let profile = Profile {
+            helm_home: Some("/profiles/client-a".to_owned()),
+            kubeconfig: Some("/kube/client-a.yaml".to_owned()),
+            environment: [("HELM_CACHE_HOME".to_owned(), "/tmp/cache".to_owned())].into_iter().collect(),
+            ..Profile::new("client A".to_owned())
+        };
+
+        let environment = profile.environment();
+
+        assert_eq!(environment["HELM_CONFIG_HOME"], Path::new("/profiles/client-a").join("config").to_string_lossy());
+        assert_eq!(environment["HELM_DATA_HOME"], Path::new("/profiles/client-a").join("data").to_string_lossy());
+        assert_eq!(environment["HELM_CACHE_HOME"], "/tmp/cache");
+        assert_eq!(environment["KUBECONFIG"], "/kube/client-a.yaml");
+        assert!(Profile::default().environment().is_empty());
+    }
+}
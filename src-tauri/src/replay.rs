@@ -0,0 +1,263 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::executor;
+
+/// Matches any single argument in fixtures, e.g. generated temporary repository names.
+pub const ANY_ARG: &'static str = "*";
+/// Stands for the system temporary directory in recorded arguments.
+const TEMP_DIR_PLACEHOLDER: &'static str = "${TMPDIR}";
+
+/// Single command execution with its outcome.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub stdout: String,
+    /// Error output when the command failed, `stdout` holds what it printed before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Interaction {
+    fn matches(&self, args: &[String]) -> bool {
+        self.args.len() == args.len() && self.args.iter()
+            .zip(args.iter())
+            .all(|(expected, actual)| expected == ANY_ARG || expected == actual)
+    }
+
+    fn result(&self) -> executor::Result<String> {
+        match self.error.clone() {
+            Some(stderr) => Err(executor::Error::CommandFailed { stdout: self.stdout.clone(), stderr }),
+            None => Ok(self.stdout.clone())
+        }
+    }
+}
+
+/// Helm invocation replayed with given output, or failed with given error output.
+#[cfg(test)]
+pub fn helm_interaction(args: &[&str], result: Result<&str, &str>) -> Interaction {
+    Interaction {
+        command: "helm".to_owned(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        stdout: result.unwrap_or_default().to_owned(),
+        error: result.err().map(|e| e.to_owned()),
+    }
+}
+
+pub fn load_fixture(path: &Path) -> std::io::Result<Vec<Interaction>> {
+    Ok(serde_json::from_str(fs::read_to_string(path)?.as_str())?)
+}
+
+pub fn save_fixture(path: &Path, interactions: &[Interaction]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(interactions)?)
+}
+
+/// Replaces machine specific temporary directory, so fixtures work everywhere.
+fn normalize(arg: &str) -> String {
+    let temp_dir = env::temp_dir().to_string_lossy().trim_end_matches(['/', '\\']).to_owned();
+    match arg.strip_prefix(temp_dir.as_str()) {
+        Some(rest) if !temp_dir.is_empty() => format!("{}{}", TEMP_DIR_PLACEHOLDER, rest.replace('\\', "/")),
+        _ => arg.to_owned()
+    }
+}
+
+/// Executes commands with inner executor and writes every interaction to a fixture file.
+pub struct RecordingExecutor {
+    inner: Box<dyn executor::Executor>,
+    path: PathBuf,
+    command: String,
+    args: Vec<String>,
+    interactions: Vec<Interaction>,
+}
+
+impl RecordingExecutor {
+    pub fn new(inner: Box<dyn executor::Executor>, path: PathBuf) -> Self {
+        RecordingExecutor {
+            inner,
+            path,
+            command: "".to_owned(),
+            args: vec![],
+            interactions: vec![],
+        }
+    }
+}
+
+impl executor::Executor for RecordingExecutor {
+    fn command(&mut self, cmd: String) -> &mut dyn executor::Executor {
+        self.command = cmd.clone();
+        self.inner.command(cmd);
+        self
+    }
+
+    fn arg(&mut self, name: String) -> &mut dyn executor::Executor {
+        self.args.push(name.clone());
+        self.inner.arg(name);
+        self
+    }
+
+    fn env(&mut self, name: String, value: String) -> &mut dyn executor::Executor {
+        self.inner.env(name, value);
+        self
+    }
+
+    fn current_dir(&mut self, dir: String) -> &mut dyn executor::Executor {
+        self.inner.current_dir(dir);
+        self
+    }
+
+    fn execute(&mut self) -> executor::Result<String> {
+        let args = std::mem::take(&mut self.args);
+        let result = self.inner.execute();
+
+        self.interactions.push(Interaction {
+            command: self.command.clone(),
+            args: args.iter().map(|a| normalize(a)).collect(),
+            stdout: match &result {
+                Ok(stdout) | Err(executor::Error::CommandFailed { stdout, .. }) => stdout.clone(),
+                Err(_) => "".to_owned(),
+            },
+            error: match &result {
+                Err(executor::Error::CannotExecuteCommand(v)) => Some(v.clone()),
+                Err(executor::Error::CommandFailed { stderr, .. }) => Some(stderr.clone()),
+                Err(executor::Error::NoCommand) => Some("no command".to_owned()),
+                Ok(_) => None,
+            },
+        });
+        if let Err(e) = save_fixture(&self.path, &self.interactions) {
+            log::error!("Cannot save fixture {}: {}", self.path.display(), e);
+        }
+        result
+    }
+}
+
+/// Answers commands with recorded interactions instead of running them.
+///
+/// Every interaction is used once in the recorded order of matching ones,
+/// when all matching ones were used the last one is repeated.
+pub struct ReplayingExecutor {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+    args: Vec<String>,
+    executed: Vec<Vec<String>>,
+}
+
+impl ReplayingExecutor {
+    pub fn new(interactions: Vec<Interaction>) -> Self {
+        ReplayingExecutor {
+            used: vec![false; interactions.len()],
+            interactions,
+            args: vec![],
+            executed: vec![],
+        }
+    }
+
+    pub fn from_fixture(path: &Path) -> std::io::Result<Self> {
+        Ok(ReplayingExecutor::new(load_fixture(path)?))
+    }
+
+    /// Arguments of all executed commands.
+    pub fn executed(&self) -> &[Vec<String>] {
+        &self.executed
+    }
+
+    /// Recorded interactions which were not replayed.
+    pub fn unused(&self) -> Vec<&Interaction> {
+        self.interactions.iter()
+            .zip(self.used.iter())
+            .filter(|(_, used)| !**used)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl executor::Executor for ReplayingExecutor {
+    fn command(&mut self, _cmd: String) -> &mut dyn executor::Executor {
+        self
+    }
+
+    fn arg(&mut self, name: String) -> &mut dyn executor::Executor {
+        self.args.push(normalize(name.as_str()));
+        self
+    }
+
+    fn env(&mut self, _name: String, _value: String) -> &mut dyn executor::Executor {
+        self
+    }
+
+    fn current_dir(&mut self, _dir: String) -> &mut dyn executor::Executor {
+        self
+    }
+
+    fn execute(&mut self) -> executor::Result<String> {
+        let args = std::mem::take(&mut self.args);
+        self.executed.push(args.clone());
+
+        let matching = self.interactions.iter()
+            .enumerate()
+            .filter(|(_, i)| i.matches(&args))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        let index = matching.iter()
+            .find(|i| !self.used[**i])
+            .or(matching.last())
+            .copied()
+            .ok_or(executor::Error::CannotExecuteCommand(format!("No recorded interaction for: {}", args.join(" "))))?;
+
+        self.used[index] = true;
+        self.interactions[index].result()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+
+    use crate::executor::{Error, Executor};
+    use crate::replay::{helm_interaction, load_fixture, RecordingExecutor, ReplayingExecutor};
+
+    #[test]
+    fn should_replay_recorded_interactions() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let path = dir.join("fixture.json");
+        let values_file = env::temp_dir().join("values.yaml").to_string_lossy().to_string();
+        let mut recorder = RecordingExecutor::new(Box::new(ReplayingExecutor::new(vec![
+            helm_interaction(&["version"], Ok("v3.12.0")),
+            helm_interaction(&["install", "web", "-f", "${TMPDIR}/values.yaml"], Err("already exists")),
+        ])), path.clone());
+
+        recorder.command("helm".to_owned()).arg("version".to_owned()).execute().unwrap();
+        recorder.command("helm".to_owned()).arg("install".to_owned()).arg("web".to_owned()).arg("-f".to_owned()).arg(values_file.clone()).execute().ok();
+        let recorded = load_fixture(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        let mut replay = ReplayingExecutor::new(recorded);
+        assert_eq!(replay.command("helm".to_owned()).arg("version".to_owned()).execute().unwrap(), "v3.12.0");
+        let failed = replay.command("helm".to_owned()).arg("install".to_owned()).arg("web".to_owned()).arg("-f".to_owned()).arg(values_file).execute();
+        assert!(matches!(failed, Err(Error::CommandFailed { stderr, .. }) if stderr == "already exists"));
+        assert!(replay.unused().is_empty());
+        assert!(replay.arg("list".to_owned()).execute().is_err());
+    }
+
+    #[test]
+    fn should_use_interactions_in_order_and_repeat_the_last_one() {
+        let mut replay = ReplayingExecutor::new(vec![
+            helm_interaction(&["repo", "ls"], Ok("first")),
+            helm_interaction(&["repo", "add", "*", "https://charts.example.com"], Ok("")),
+            helm_interaction(&["repo", "ls"], Ok("second")),
+        ]);
+
+        let mut ls = || replay.arg("repo".to_owned()).arg("ls".to_owned()).execute().unwrap();
+        assert_eq!(vec![ls(), ls(), ls()], vec!["first", "second", "second"]);
+        replay.arg("repo".to_owned()).arg("add".to_owned()).arg("tmp-1".to_owned()).arg("https://charts.example.com".to_owned()).execute().unwrap();
+        assert_eq!(replay.executed().len(), 4);
+    }
+}
@@ -0,0 +1,440 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::executor;
+use crate::executor::Executor;
+use crate::helm;
+use crate::profile::{DEFAULT_PROFILE, Profile};
+
+/// Version of settings file layout, bump it together with adding a step to [MIGRATIONS].
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Step `i` upgrades settings file from version `i` to `i + 1`.
+const MIGRATIONS: [fn(&mut serde_json::Map<String, Value>); CURRENT_VERSION as usize] = [
+    migrate_unversioned,
+    migrate_to_profiles,
+];
+
+const DEFAULT_MAX_COMMANDS_PER_CONTEXT: usize = 4;
+const DEFAULT_NOTIFICATION_MINIMUM_SECONDS: u64 = 10;
+
+#[derive(Debug)]
+pub enum Error {
+    CannotAccessSettings(String),
+    UnsupportedVersion(u32),
+    InvalidProfile(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::CannotAccessSettings(value.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::CannotAccessSettings(value.to_string())
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::CannotAccessSettings(v) => write!(f, "Cannot access settings: {}", v),
+            Error::UnsupportedVersion(v) => write!(f, "Settings version {} is newer than supported {}", v, CURRENT_VERSION),
+            Error::InvalidProfile(v) => write!(f, "Invalid profile: {}", v),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    #[serde(rename = "system")]
+    System,
+    #[serde(rename = "light")]
+    Light,
+    #[serde(rename = "dark")]
+    Dark,
+}
+
+/// Which operations show system notification when they are finished.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub install: bool,
+    pub uninstall: bool,
+    pub import: bool,
+    pub batch: bool,
+    /// Operations finished sooner are not notified, user is still looking at them.
+    #[serde(rename = "minimumDurationSeconds")]
+    pub minimum_duration_seconds: u64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            install: true,
+            uninstall: true,
+            import: true,
+            batch: true,
+            minimum_duration_seconds: DEFAULT_NOTIFICATION_MINIMUM_SECONDS,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    /// Path to helm binary, helm from PATH when not set.
+    #[serde(rename = "helmBinary")]
+    pub helm_binary: Option<String>,
+    /// Helm commands running longer are killed, commands changing releases get it as helm `--timeout` instead.
+    /// No limit when not set.
+    #[serde(rename = "commandTimeoutSeconds")]
+    pub command_timeout_seconds: Option<u64>,
+    #[serde(rename = "refreshIntervalSeconds")]
+    pub refresh_interval_seconds: u64,
+    /// Helm commands running at once against one cluster, others wait for a free slot.
+    #[serde(rename = "maxCommandsPerContext")]
+    pub max_commands_per_context: usize,
+    pub profiles: Vec<Profile>,
+    #[serde(rename = "activeProfile")]
+    pub active_profile: String,
+    pub theme: Theme,
+    pub notifications: NotificationSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: CURRENT_VERSION,
+            helm_binary: None,
+            command_timeout_seconds: None,
+            refresh_interval_seconds: helm::watcher::DEFAULT_INTERVAL_SECONDS,
+            max_commands_per_context: DEFAULT_MAX_COMMANDS_PER_CONTEXT,
+            profiles: vec![Profile::default()],
+            active_profile: DEFAULT_PROFILE.to_owned(),
+            theme: Theme::System,
+            notifications: NotificationSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Active profile, settings are never stored with active profile missing, see [Settings::check_active_profile].
+    pub fn profile(&self) -> Profile {
+        self.profiles.iter()
+            .find(|p| p.name == self.active_profile)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check_active_profile(&self) -> Result<()> {
+        match self.profiles.iter().any(|p| p.name == self.active_profile) {
+            true => Ok(()),
+            false => Err(Error::InvalidProfile(format!("active profile {} does not exist", self.active_profile))),
+        }
+    }
+
+    /// Switches edited settings file with missing active profile to the default profile, so commands do not run
+    /// against a cluster nobody chose without a trace.
+    fn repair_active_profile(&mut self) {
+        if self.check_active_profile().is_ok() {
+            return;
+        }
+        log::warn!("Active profile {} does not exist, switching to {}", self.active_profile, DEFAULT_PROFILE);
+        if !self.profiles.iter().any(|p| p.name == DEFAULT_PROFILE) {
+            self.profiles.insert(0, Profile::default());
+        }
+        self.active_profile = DEFAULT_PROFILE.to_owned();
+    }
+
+    pub fn add_profile(&mut self, profile: Profile) -> Result<()> {
+        if profile.name.trim().is_empty() {
+            return Err(Error::InvalidProfile("name cannot be empty".to_owned()));
+        }
+        if self.profiles.iter().any(|p| p.name == profile.name) {
+            return Err(Error::InvalidProfile(format!("{} already exists", profile.name)));
+        }
+        self.profiles.push(profile);
+        Ok(())
+    }
+
+    pub fn update_profile(&mut self, profile: Profile) -> Result<()> {
+        let existing = self.profiles.iter_mut()
+            .find(|p| p.name == profile.name)
+            .ok_or(Error::InvalidProfile(format!("{} does not exist", profile.name)))?;
+        *existing = profile;
+        Ok(())
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        if name == DEFAULT_PROFILE || name == self.active_profile {
+            return Err(Error::InvalidProfile(format!("{} cannot be removed while in use", name)));
+        }
+        self.profiles.retain(|p| p.name != name);
+        Ok(())
+    }
+
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        if !self.profiles.iter().any(|p| p.name == name) {
+            return Err(Error::InvalidProfile(format!("{} does not exist", name)));
+        }
+        self.active_profile = name.to_owned();
+        Ok(())
+    }
+
+    /// Executor with timeout, concurrency limit and environment of the active profile.
+    pub fn executor(&self) -> executor::DefaultExecutor {
+        let mut executor = executor::DefaultExecutor::new()
+            .with_timeout(self.command_timeout_seconds.map(Duration::from_secs))
+            .with_limit_per_context(Some(self.max_commands_per_context));
+        for (name, value) in self.profile().environment() {
+            executor.env(name, value);
+        }
+        executor
+    }
+
+    /// Helm API using configured helm binary and kube context of the active profile.
+    pub fn api<'a>(&self, executor: &'a mut dyn executor::Executor) -> helm::Api<'a> {
+        self.configure(helm::Api::new(executor))
+    }
+
+    pub fn configure<'a>(&self, api: helm::Api<'a>) -> helm::Api<'a> {
+        let profile = self.profile();
+        api.with_helm_binary(self.helm_binary.clone())
+            .with_operation_timeout(self.command_timeout_seconds.map(Duration::from_secs))
+            .with_kube_context(profile.kube_context)
+            .with_keyring(profile.keyring)
+            .with_signed_charts_only(profile.require_signed_charts)
+    }
+}
+
+/// Settings file parsed and upgraded to [CURRENT_VERSION].
+pub fn migrate(content: &str) -> Result<Settings> {
+    let mut value: Value = serde_json::from_str(content)?;
+    let object = value.as_object_mut()
+        .ok_or(Error::CannotAccessSettings("settings file is not an object".to_owned()))?;
+
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > CURRENT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    for migration in MIGRATIONS[version as usize..].iter() {
+        migration(object);
+    }
+    object.insert("version".to_owned(), Value::from(CURRENT_VERSION));
+    let mut settings: Settings = serde_json::from_value(value)?;
+    settings.repair_active_profile();
+    Ok(settings)
+}
+
+/// Files without `version` field have the same layout as version 1.
+fn migrate_unversioned(_settings: &mut serde_json::Map<String, Value>) {}
+
+/// Kube context, default namespace and environment moved from settings into the default profile.
+fn migrate_to_profiles(settings: &mut serde_json::Map<String, Value>) {
+    let mut profile = serde_json::Map::new();
+    profile.insert("name".to_owned(), Value::from(DEFAULT_PROFILE));
+    for key in ["kubeContext", "defaultNamespace", "environment"] {
+        if let Some(value) = settings.remove(key) {
+            profile.insert(key.to_owned(), value);
+        }
+    }
+    settings.insert("profiles".to_owned(), Value::Array(vec![Value::Object(profile)]));
+    settings.insert("activeProfile".to_owned(), Value::from(DEFAULT_PROFILE));
+}
+
+/// Settings kept in memory and persisted as JSON file.
+#[derive(Clone)]
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Arc<Mutex<Settings>>,
+    /// Unset when unreadable settings file could not be backed up, saving would lose it.
+    writable: bool,
+}
+
+impl SettingsStore {
+    /// Reads settings from `path`, defaults are used when file does not exist or cannot be read.
+    ///
+    /// File which cannot be read, e.g. written by newer version, is copied to `<file>.bak` before defaults replace it.
+    pub fn load(path: PathBuf) -> Self {
+        let mut writable = true;
+        let settings = match fs::read_to_string(&path) {
+            Ok(content) => migrate(content.as_str()).unwrap_or_else(|e| {
+                let backup = backup_path(&path);
+                match fs::copy(&path, &backup) {
+                    Ok(_) => log::error!("{}, using defaults, previous settings are kept in {}", e, backup.display()),
+                    Err(copy_error) => {
+                        log::error!("{}, using defaults without saving them, cannot back up settings: {}", e, copy_error);
+                        writable = false;
+                    }
+                }
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+        SettingsStore {
+            path,
+            settings: Arc::new(Mutex::new(settings)),
+            writable,
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&self, settings: Settings) -> Result<Settings> {
+        self.modify(|current| {
+            *current = settings;
+            Ok(())
+        })
+    }
+
+    /// Applies `change` to current settings and persists them, nothing is changed when it fails.
+    pub fn modify<F>(&self, change: F) -> Result<Settings>
+        where
+            F: FnOnce(&mut Settings) -> Result<()>,
+    {
+        if !self.writable {
+            return Err(Error::CannotAccessSettings(format!("{} could not be backed up, it is not overwritten", self.path.display())));
+        }
+        let mut current = self.settings.lock().map_err(|e| Error::CannotAccessSettings(e.to_string()))?;
+        let mut settings = current.clone();
+        change(&mut settings)?;
+        settings.check_active_profile()?;
+        settings.version = CURRENT_VERSION;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&settings)?)?;
+        *current = settings.clone();
+        Ok(settings)
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.bak", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+
+    use crate::profile::{DEFAULT_PROFILE, Profile};
+    use crate::settings::{CURRENT_VERSION, Error, migrate, Settings, SettingsStore, Theme};
+
+    #[test]
+    fn should_migrate_unversioned_settings() {
+        let settings = migrate(r#"{"theme": "dark", "notifications": {"install": false, "uninstall": true, "import": true, "batch": true}}"#).unwrap();
+
+        assert_eq!(settings.version, CURRENT_VERSION);
+        assert_eq!(settings.theme, Theme::Dark);
+        assert!(!settings.notifications.install);
+        assert_eq!(settings.notifications.minimum_duration_seconds, 10);
+        assert_eq!(settings.command_timeout_seconds, Settings::default().command_timeout_seconds);
+        assert_eq!(settings.refresh_interval_seconds, Settings::default().refresh_interval_seconds);
+        assert!(matches!(migrate(r#"{"version": 99}"#), Err(Error::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn should_persist_updated_settings() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let path = dir.join("settings.json");
+        let store = SettingsStore::load(path.clone());
+        assert_eq!(store.get(), Settings::default());
+
+        store.update(Settings {
+            helm_binary: Some("/opt/helm/helm".to_owned()),
+            refresh_interval_seconds: 30,
+            ..store.get()
+        }).unwrap();
+        let reloaded = SettingsStore::load(path).get();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(reloaded.helm_binary, Some("/opt/helm/helm".to_owned()));
+        assert_eq!(reloaded.refresh_interval_seconds, 30);
+    }
+
+    #[test]
+    fn should_back_up_unsupported_settings_before_saving() {
+        let dir = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        let original = r#"{"version": 99, "profiles": [{"name": "from-newer-version"}]}"#;
+        fs::write(&path, original).unwrap();
+
+        let store = SettingsStore::load(path.clone());
+        store.modify(|s| {
+            s.theme = Theme::Dark;
+            Ok(())
+        }).unwrap();
+        let backup = fs::read_to_string(dir.join("settings.json.bak")).unwrap();
+        let saved = SettingsStore::load(path).get();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(backup, original);
+        assert_eq!(saved.theme, Theme::Dark);
+    }
+
+    #[test]
+    fn should_move_cluster_settings_into_default_profile() {
+        let settings = migrate(r#"{
+            "version": 1,
+            "kubeContext": "prod",
+            "defaultNamespace": "apps",
+            "environment": {"HELM_DRIVER": "configmap"}
+        }"#).unwrap();
+
+        assert_eq!(settings.active_profile, DEFAULT_PROFILE);
+        assert_eq!(settings.profiles.len(), 1);
+        assert_eq!(settings.profile().kube_context, Some("prod".to_owned()));
+        assert_eq!(settings.profile().default_namespace, Some("apps".to_owned()));
+        assert_eq!(settings.profile().environment["HELM_DRIVER"], "configmap");
+    }
+
+    #[test]
+    fn should_keep_active_profile_existing() {
+        let repaired = migrate(r#"{"version": 2, "profiles": [{"name": "client A"}], "activeProfile": "client B"}"#).unwrap();
+        let path = env::temp_dir().join(uuid::Uuid::new_v4().to_string()).join("settings.json");
+        let store = SettingsStore::load(path.clone());
+
+        let updated = store.update(Settings { active_profile: "client B".to_owned(), ..Settings::default() });
+
+        assert_eq!(repaired.active_profile, DEFAULT_PROFILE);
+        assert_eq!(repaired.profiles.len(), 2);
+        assert!(matches!(updated, Err(Error::InvalidProfile(_))));
+        assert_eq!(store.get().active_profile, DEFAULT_PROFILE);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn should_switch_between_profiles() {
+        let mut settings = Settings::default();
+        settings.add_profile(Profile { kube_context: Some("client-a".to_owned()), ..Profile::new("client A".to_owned()) }).unwrap();
+
+        assert!(settings.add_profile(Profile::new("client A".to_owned())).is_err());
+        assert!(settings.switch_profile("unknown").is_err());
+        settings.switch_profile("client A").unwrap();
+        assert_eq!(settings.profile().kube_context, Some("client-a".to_owned()));
+        assert!(settings.remove_profile("client A").is_err());
+        assert!(settings.remove_profile(DEFAULT_PROFILE).is_err());
+
+        settings.switch_profile(DEFAULT_PROFILE).unwrap();
+        settings.remove_profile("client A").unwrap();
+        assert_eq!(settings.profiles, vec![Profile::default()]);
+    }
+}
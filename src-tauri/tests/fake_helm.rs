@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use helmik::executor::{DefaultExecutor, Executor};
+use helmik::helm::Api;
+use helmik::helm::cmd::InstallChartRequest;
+
+/// Runs fake helm the way `helm::Cmd` runs helm.
+fn helm(state: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_fake-helm"))
+        .env("FAKE_HELM_STATE", state)
+        .args(args)
+        .output()
+        .expect("Cannot run fake helm");
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(String::from_utf8_lossy(&output.stderr).to_string()),
+    }
+}
+
+/// Directory of a single test with a fresh copy of the fixture state.
+fn state_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fake-helm-{}-{}", test, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fake-helm/state.json"), dir.join("state.json")).unwrap();
+    dir
+}
+
+/// Executor passing fake helm its state, the way settings point Helmik at a helm binary.
+fn executor(dir: &Path) -> DefaultExecutor {
+    let mut executor = DefaultExecutor::new();
+    executor.env("FAKE_HELM_STATE".to_owned(), dir.join("state.json").to_string_lossy().to_string());
+    executor
+}
+
+fn api(executor: &mut DefaultExecutor) -> Api<'_> {
+    Api::new(executor).with_helm_binary(Some(env!("CARGO_BIN_EXE_fake-helm").to_owned()))
+}
+
+fn install_request(name: &str, version: Option<&str>, values: Option<&str>) -> InstallChartRequest {
+    InstallChartRequest {
+        name: name.to_owned(),
+        chart_name: "bitnami/nginx".to_owned(),
+        namespace: "default".to_owned(),
+        version: version.map(|v| v.to_owned()),
+        values: values.map(|v| v.to_owned()),
+        verify: false,
+    }
+}
+
+#[test]
+fn should_keep_state_between_invocations() {
+    let dir = state_dir("invocations");
+    let state = dir.join("state.json");
+    let values = dir.join("values.yaml");
+    fs::write(&values, "replicaCount: 2\n").unwrap();
+
+    helm(&state, &["repo", "add", "bitnami", "https://charts.bitnami.com/bitnami"]).unwrap();
+    helm(&state, &["install", "web", "bitnami/nginx", "--namespace", "default", "--create-namespace", "--version", "15.0.2", "-f", values.to_str().unwrap()]).unwrap();
+    helm(&state, &["upgrade", "web", "bitnami/nginx", "-n", "default", "--reuse-values"]).unwrap();
+    let error = helm(&state, &["uninstall", "cache", "-n", "default"]).unwrap_err();
+    let releases: serde_json::Value = serde_json::from_str(helm(&state, &["list", "-A", "-o", "json"]).unwrap().as_str()).unwrap();
+    let values = helm(&state, &["get", "values", "web", "-n", "default", "-o", "yaml"]).unwrap();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(error.starts_with("Error: uninstall"));
+    assert_eq!(releases[0]["chart"], "nginx-15.1.0");
+    assert_eq!(releases[0]["revision"], "2");
+    assert_eq!(values, "replicaCount: 2\n");
+}
+
+#[test]
+fn should_manage_release_lifecycle_through_api() {
+    let dir = state_dir("lifecycle");
+    let mut executor = executor(&dir);
+    let mut api = api(&mut executor);
+
+    api.add_repository("bitnami".to_owned(), "https://charts.bitnami.com/bitnami".to_owned()).unwrap();
+    let repos = api.repos().unwrap();
+    api.install_chart(install_request("lifecycle", Some("15.0.2"), Some("replicaCount: 2\n"))).unwrap();
+    let installed = api.list().unwrap();
+    let first_rollback = api.rollback("lifecycle".to_owned(), "default".to_owned(), None);
+
+    api.upgrade_to_latest("lifecycle".to_owned(), "default".to_owned()).unwrap();
+    let upgraded = api.list().unwrap();
+    let values = api.get_values("lifecycle".to_owned(), "default".to_owned()).unwrap();
+
+    api.rollback("lifecycle".to_owned(), "default".to_owned(), None).unwrap();
+    let rolled_back = api.list().unwrap();
+    api.uninstall_chart("lifecycle".to_owned(), "default".to_owned()).unwrap();
+    let uninstalled = api.list().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(repos[0].name, "bitnami");
+    assert_eq!((installed[0].revision, installed[0].chart.as_str()), (1, "nginx-15.0.2"));
+    assert!(format!("{:?}", first_rollback.unwrap_err()).contains("release has no 0 version"));
+    assert_eq!((upgraded[0].revision, upgraded[0].chart.as_str()), (2, "nginx-15.1.0"));
+    assert_eq!(values, "replicaCount: 2\n");
+    assert_eq!((rolled_back[0].revision, rolled_back[0].chart.as_str()), (3, "nginx-15.0.2"));
+    assert!(uninstalled.is_empty());
+}
+
+#[test]
+fn should_apply_release_repeatedly_through_api() {
+    let dir = state_dir("apply");
+    let mut executor = executor(&dir);
+    let mut api = api(&mut executor).with_operation_timeout(Some(std::time::Duration::from_secs(300)));
+
+    api.add_repository("bitnami".to_owned(), "https://charts.bitnami.com/bitnami".to_owned()).unwrap();
+    api.apply_release(install_request("apply", Some("15.0.2"), None)).unwrap();
+    api.apply_release(install_request("apply", Some("15.1.0"), None)).unwrap();
+    let releases = api.list().unwrap();
+    let duplicate = api.install_chart(install_request("apply", None, None));
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(releases.len(), 1);
+    assert_eq!((releases[0].revision, releases[0].chart.as_str()), (2, "nginx-15.1.0"));
+    assert!(duplicate.is_err());
+}
@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/tauri";
-import { ChartDetails, ChartListItem, InstallChartRequest, Release, Repo } from "./model";
+import { open } from "@tauri-apps/api/dialog";
+import { AuditEntry, AuditQuery, BatchAction, BatchResult, BatchTarget, ChartDetails, ChartListItem, DependencyState, GitOpsFormat, HelmfilePlan, HelmInstallation, InstallChartRequest, LintRequest, LintResult, LogLevel, LogLine, PackageRequest, PackageResult, Profile, PublishRequest, PublishResult, Release, ReleaseUpdate, Repo, RunningOperation, SearchResult, Settings, SetupStatus, SubchartState, VerificationResult } from "./model";
 
 export namespace api {
     export async function list_releases(): Promise<Release[]> {
@@ -30,7 +31,7 @@ export namespace api {
         return invoke("refresh_repositories", {});
     }
 
-    export async function findChartsInRepo(filter: string): Promise<ChartListItem[]> {
+    export async function findChartsInRepo(filter: string): Promise<SearchResult> {
         return invoke("find_charts_in_repo", {filter: filter});
     }
 
@@ -38,10 +39,63 @@ export namespace api {
         return invoke("get_chart_details", {name: name});
     }
 
+    export async function getChartVersions(chartName: string): Promise<ChartListItem[]> {
+        return invoke("get_chart_versions", {chartName: chartName});
+    }
+
+    export async function pullChart(chartName: string, version: string | null, destination: string, verify: boolean): Promise<string> {
+        return invoke("pull_chart", {chartName: chartName, version: version, destination: destination, verify: verify});
+    }
+
+    export async function verifyChart(archive: string): Promise<VerificationResult> {
+        return invoke("verify_chart", {archive: archive});
+    }
+
     export async function installChart(req: InstallChartRequest): Promise<void> {
         return invoke("install_chart", {request: req})
     }
 
+    export async function templateChart(req: InstallChartRequest): Promise<string> {
+        return invoke("template_chart", {request: req})
+    }
+
+    export async function selectLocalChart(packaged: boolean): Promise<string | null> {
+        const selected = await open({
+            directory: !packaged,
+            multiple: false,
+            filters: packaged ? [{name: "Helm chart", extensions: ["tgz"]}] : undefined,
+        });
+        return typeof selected === "string" ? selected : null;
+    }
+
+    export async function listChartDependencies(chartPath: string): Promise<DependencyState[]> {
+        return invoke("list_chart_dependencies", {chartPath: chartPath});
+    }
+
+    export async function updateChartDependencies(chartPath: string): Promise<DependencyState[]> {
+        return invoke("update_chart_dependencies", {chartPath: chartPath});
+    }
+
+    export async function buildChartDependencies(chartPath: string): Promise<DependencyState[]> {
+        return invoke("build_chart_dependencies", {chartPath: chartPath});
+    }
+
+    export async function resolveSubcharts(chartPath: string, values: string | null): Promise<SubchartState[]> {
+        return invoke("resolve_subcharts", {chartPath: chartPath, values: values});
+    }
+
+    export async function lintChart(request: LintRequest): Promise<LintResult> {
+        return invoke("lint_chart", {request: request});
+    }
+
+    export async function packageChart(request: PackageRequest): Promise<PackageResult> {
+        return invoke("package_chart", {request: request});
+    }
+
+    export async function publishChart(request: PublishRequest): Promise<PublishResult> {
+        return invoke("publish_chart", {request: request});
+    }
+
     export async function uninstallChart(releaseName: string, namespace: string): Promise<void> {
         return invoke("uninstall_chart", {releaseName: releaseName, namespace: namespace});
     }
@@ -49,4 +103,104 @@ export namespace api {
     export async function getValues(releaseName: string, namespace: string): Promise<string> {
         return invoke("get_values", {releaseName: releaseName, namespace: namespace});
     }
+
+    export async function exportRelease(releaseName: string, namespace: string): Promise<string> {
+        return invoke("export_release", {releaseName: releaseName, namespace: namespace});
+    }
+
+    export async function importRelease(bundle: string): Promise<void> {
+        return invoke("import_release", {bundle: bundle});
+    }
+
+    export async function exportGitOps(releaseName: string, namespace: string, format: GitOpsFormat, directory?: string): Promise<string> {
+        return invoke("export_gitops", {releaseName: releaseName, namespace: namespace, format: format, directory: directory});
+    }
+
+    export async function batchOperation(targets: BatchTarget[], action: BatchAction, concurrency?: number): Promise<BatchResult[]> {
+        return invoke("batch_operation", {targets: targets, action: action, concurrency: concurrency});
+    }
+
+    export async function loadHelmfile(path: string): Promise<HelmfilePlan> {
+        return invoke("load_helmfile", {path: path});
+    }
+
+    export async function applyHelmfile(path: string): Promise<void> {
+        return invoke("apply_helmfile", {path: path});
+    }
+
+    export async function exportHelmfile(): Promise<string> {
+        return invoke("export_helmfile", {});
+    }
+
+    export async function checkReleaseUpdates(): Promise<ReleaseUpdate[]> {
+        return invoke("check_release_updates", {});
+    }
+
+    export async function startReleaseWatcher(kubeContext?: string, intervalSeconds?: number): Promise<void> {
+        return invoke("start_release_watcher", {kubeContext: kubeContext, intervalSeconds: intervalSeconds});
+    }
+
+    export async function stopReleaseWatcher(kubeContext?: string): Promise<void> {
+        return invoke("stop_release_watcher", {kubeContext: kubeContext});
+    }
+
+    export async function getSettings(): Promise<Settings> {
+        return invoke("get_settings", {});
+    }
+
+    export async function updateSettings(settings: Settings): Promise<Settings> {
+        return invoke("update_settings", {settings: settings});
+    }
+
+    export async function queryAuditLog(query: AuditQuery): Promise<AuditEntry[]> {
+        return invoke("query_audit_log", {query: query});
+    }
+
+    export async function exportAuditLog(query: AuditQuery, path: string): Promise<void> {
+        return invoke("export_audit_log", {query: query, path: path});
+    }
+
+    export async function createProfile(name: string, isolated: boolean): Promise<Settings> {
+        return invoke("create_profile", {name: name, isolated: isolated});
+    }
+
+    export async function updateProfile(profile: Profile): Promise<Settings> {
+        return invoke("update_profile", {profile: profile});
+    }
+
+    export async function deleteProfile(name: string): Promise<Settings> {
+        return invoke("delete_profile", {name: name});
+    }
+
+    export async function switchProfile(name: string): Promise<Settings> {
+        return invoke("switch_profile", {name: name});
+    }
+
+    export async function helmEnv(): Promise<Record<string, string>> {
+        return invoke("helm_env", {});
+    }
+
+    export async function runningOperations(): Promise<RunningOperation[]> {
+        return invoke("running_operations", {});
+    }
+
+    export async function cancelOperation(id: number): Promise<boolean> {
+        return invoke("cancel_operation", {id: id});
+    }
+
+    export async function recentLogs(limit?: number, level?: LogLevel): Promise<LogLine[]> {
+        return invoke("recent_logs", {limit: limit, level: level});
+    }
+
+    export async function discoverHelmBinaries(): Promise<HelmInstallation[]> {
+        return invoke("discover_helm_binaries", {});
+    }
+
+    export async function setHelmBinary(path?: string): Promise<SetupStatus> {
+        return invoke("set_helm_binary", {path: path});
+    }
+
+    export async function checkHelmSetup(): Promise<SetupStatus> {
+        return invoke("check_helm_setup", {});
+    }
 }
\ No newline at end of file
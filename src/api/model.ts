@@ -1,13 +1,22 @@
 export type Release = {
     name: string,
     namespace: string,
-    revision: string,
+    revision: number,
     updated: string,
-    status: string,
+    status: ReleaseStatus,
     chart: string,
+    chartReference?: ChartReference,
     appVersion: string,
 }
 
+export type ReleaseStatus = "deployed" | "uninstalled" | "superseded" | "failed" | "uninstalling"
+    | "pending-install" | "pending-upgrade" | "pending-rollback" | "unknown";
+
+export type ChartReference = {
+    name: string,
+    version: string,
+}
+
 export type Repo = {
     name: string,
     url: string
@@ -20,10 +29,24 @@ export type ChartListItem = {
     description: string,
 }
 
+export type SkippedRepository = {
+    name: string,
+    reason: string,
+}
+
+export type SearchResult = {
+    charts: ChartListItem[],
+    skipped: SkippedRepository[],
+}
+
 export type ChartDetails = {
     info: ChartInfo
     readme: string;
     values: string;
+    valuesSchema?: string;
+    templates: string[];
+    crds: string[];
+    subcharts: ChartInfo[];
 }
 
 export type ChartInfo = {
@@ -54,6 +77,83 @@ export type ChartDependency = {
     enabled?: boolean;
     alis?: string;
 }
+export type DependencyStatus = "ok" | "missing" | "wrongVersion" | "unpacked" | "misnamed" | "invalid";
+
+export type DependencyState = {
+    name: string;
+    version: string;
+    repository: string;
+    status: DependencyStatus;
+    statusText: string;
+}
+
+export type SubchartState = {
+    name: string;
+    alias?: string;
+    condition?: string;
+    tags: string[];
+    enabled: boolean;
+    resolvedBy: "condition" | "tags" | "default";
+}
+
+export type LintRequest = {
+    chartPath: string;
+    valuesFiles: string[];
+    strict: boolean;
+}
+
+export type LintFinding = {
+    severity: "info" | "warning" | "error";
+    path?: string;
+    message: string;
+}
+
+export type LintResult = {
+    passed: boolean;
+    findings: LintFinding[];
+}
+
+export type SignOptions = {
+    key: string;
+    keyring: string;
+    passphraseFile?: string;
+}
+
+export type PackageRequest = {
+    chartPath: string;
+    destination?: string;
+    version?: string;
+    appVersion?: string;
+    sign?: SignOptions;
+}
+
+export type PackageResult = {
+    path: string;
+    digest: string;
+    provenance?: string;
+}
+
+export type PublishRequest = {
+    archive: string;
+    url: string;
+    username?: string;
+    password?: string;
+}
+
+export type PublishResult = {
+    package: PackageResult;
+    location: string;
+    manifestDigest?: string;
+}
+
+export type VerificationResult = {
+    status: "verified" | "unsigned" | "invalid";
+    signer?: string;
+    fingerprint?: string;
+    hash?: string;
+    message?: string;
+}
+
 export type ChartMaintainer = {
     name: string;
     url?: string;
@@ -64,5 +164,139 @@ export type InstallChartRequest = {
     name: string;
     chartName: string;
     namespace: string;
+    version?: string;
     values: string;
-}
\ No newline at end of file
+    verify?: boolean;
+}
+
+export type HelmfilePlan = {
+    repositories: Repo[];
+    releases: InstallChartRequest[];
+}
+
+export type GitOpsFormat = "argocd" | "flux";
+
+export type BatchTarget = {
+    release: string;
+    namespace: string;
+}
+
+export type BatchAction = "uninstall" | "upgradeLatest" | "rollback" | "refreshValues";
+
+export type BatchResult = {
+    target: BatchTarget;
+    success: boolean;
+    message?: string;
+}
+
+export type UpgradeLevel = "patch" | "minor" | "major";
+
+export type ReleaseUpdate = {
+    release: string;
+    namespace: string;
+    chartName: string;
+    repository?: string;
+    currentVersion: string;
+    latestVersion?: string;
+    currentAppVersion: string;
+    latestAppVersion?: string;
+    upgradeLevel?: UpgradeLevel;
+    appVersionChanged: boolean;
+    ambiguousRepositories: string[];
+}
+
+export type ReleaseChange =
+    | { type: "added", release: Release }
+    | { type: "removed", release: Release }
+    | { type: "upgraded", previousRevision: number, release: Release }
+    | { type: "statusChanged", previousStatus: ReleaseStatus, release: Release };
+
+export type ReleaseChangeEvent = {
+    kubeContext?: string;
+    change: ReleaseChange;
+}
+
+export type OperationKind = "install" | "uninstall" | "import" | "batch";
+
+export type RunningOperation = {
+    id: number;
+    kind: OperationKind;
+    subject: string;
+    startedAt: string;
+}
+
+export type NotificationSettings = {
+    install: boolean;
+    uninstall: boolean;
+    import: boolean;
+    batch: boolean;
+    minimumDurationSeconds: number;
+}
+
+export type AuditEntry = {
+    timestamp: string;
+    user?: string;
+    kubeContext?: string;
+    namespace?: string;
+    operation: string;
+    arguments: string[];
+    success: boolean;
+    stderr?: string;
+}
+
+export type AuditQuery = {
+    from?: string;
+    to?: string;
+    operation?: string;
+    namespace?: string;
+    success?: boolean;
+    limit?: number;
+}
+
+export type LogLevel = "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE";
+
+export type LogLine = {
+    timestamp: string;
+    level: LogLevel;
+    target: string;
+    message: string;
+}
+
+export type HelmInstallation = {
+    path: string;
+    version?: string;
+    supported: boolean;
+    error?: string;
+}
+
+export type SetupStatus = {
+    ready: boolean;
+    installation: HelmInstallation;
+    minimumVersion: string;
+    message?: string;
+}
+
+export type Theme = "system" | "light" | "dark";
+
+export type Profile = {
+    name: string;
+    helmHome?: string;
+    kubeconfig?: string;
+    kubeContext?: string;
+    defaultNamespace?: string;
+    environment: Record<string, string>;
+    keyring?: string;
+    requireSignedCharts: boolean;
+}
+
+export type Settings = {
+    version: number;
+    helmBinary?: string;
+    commandTimeoutSeconds?: number;
+    refreshIntervalSeconds: number;
+    maxCommandsPerContext: number;
+    profiles: Profile[];
+    activeProfile: string;
+    theme: Theme;
+    notifications: NotificationSettings;
+}
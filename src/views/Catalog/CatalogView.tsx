@@ -22,7 +22,10 @@ const CatalogView = () => {
         setLoading(true);
         try {
             const data = await api.findChartsInRepo(filter);
-            setCharts(data);
+            setCharts(data.charts);
+            if (data.skipped.length > 0) {
+                applicationStore.reportAnError("Repositories not searched: " + data.skipped.map(r => `${r.name} (${r.reason})`).join(", "));
+            }
         } catch (err: any) {
             applicationStore.reportAnError(err);
         } finally {